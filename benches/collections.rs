@@ -0,0 +1,211 @@
+// compares BinTreeMap/BinTreeOrderedSet (and the raw BinTree without rebalancing)
+// with the std collections for a few key orders
+// cargo bench (or cargo bench -- insert/random to filter)
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hint::black_box;
+
+use bin_tree::{BinTree, BinTreeMap, BinTreeOrderedSet};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// number of keys for the balanced containers
+const N: u64 = 10_000;
+/// without rebalancing, sorted input degenerates into a list (quadratic build, deep recursion)
+const N_UNBALANCED: u64 = 2_000;
+
+/// key orders: already sorted, shuffled, and zigzag (alternating from both ends)
+const ORDERS: [&str; 3] = ["sequential", "random", "adversarial"];
+
+fn keys(order: &str, n: u64) -> Vec<u64> {
+    match order {
+        "sequential" => (0..n).collect(),
+        "random" => {
+            // deterministic Fisher-Yates shuffle (xorshift) so that runs are comparable
+            let mut keys: Vec<u64> = (0..n).collect();
+            let mut state = 0x2545_f491_4f6c_dd1d_u64;
+            for i in (1..keys.len()).rev() {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                keys.swap(i, (state % (i as u64 + 1)) as usize);
+            }
+            keys
+        },
+        "adversarial" => (0..n).map(|i| if i % 2 == 0 { i / 2 } else { n - 1 - i / 2 }).collect(),
+        _ => unreachable!("unknown key order"),
+    }
+}
+
+fn unbalanced_insert(tree: &mut BinTree<u64>, key: u64) {
+    tree.insert_to_key_cmp(key, |k| k, u64::partial_cmp, false, true);
+}
+
+fn build_map(keys: &[u64]) -> BinTreeMap<u64, u64> {
+    let mut map = BinTreeMap::new();
+    for &k in keys { map.insert(k, k); }
+    map
+}
+
+fn build_unbalanced(keys: &[u64]) -> BinTree<u64> {
+    let mut tree = BinTree::new();
+    for &k in keys { unbalanced_insert(&mut tree, k); }
+    tree
+}
+
+fn insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert");
+    for order in ORDERS {
+        let keys = keys(order, N);
+        group.bench_with_input(BenchmarkId::new("BinTreeMap", order), &keys, |b, keys| {
+            b.iter(|| build_map(keys))
+        });
+        group.bench_with_input(BenchmarkId::new("BinTreeOrderedSet", order), &keys, |b, keys| {
+            b.iter(|| {
+                let mut set = BinTreeOrderedSet::new();
+                for &k in keys { set.insert(k); }
+                set
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("BTreeMap", order), &keys, |b, keys| {
+            b.iter(|| {
+                let mut map = BTreeMap::new();
+                for &k in keys { map.insert(k, k); }
+                map
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("HashMap", order), &keys, |b, keys| {
+            b.iter(|| {
+                let mut map = HashMap::new();
+                for &k in keys { map.insert(k, k); }
+                map
+            })
+        });
+        let keys = self::keys(order, N_UNBALANCED);
+        group.bench_with_input(BenchmarkId::new("BinTree (no rebalance)", order), &keys, |b, keys| {
+            b.iter(|| build_unbalanced(keys))
+        });
+    }
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+    for order in ORDERS {
+        let keys = keys(order, N);
+        let map = build_map(&keys);
+        group.bench_with_input(BenchmarkId::new("BinTreeMap", order), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|k| map.contains_key(k)).count())
+        });
+        let set: BinTreeOrderedSet<u64> = keys.iter().copied().collect();
+        group.bench_with_input(BenchmarkId::new("BinTreeOrderedSet", order), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|k| set.contains(k)).count())
+        });
+        let btree: BTreeMap<u64, u64> = keys.iter().map(|&k| (k, k)).collect();
+        group.bench_with_input(BenchmarkId::new("BTreeMap", order), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|k| btree.contains_key(k)).count())
+        });
+        let hash: HashMap<u64, u64> = keys.iter().map(|&k| (k, k)).collect();
+        group.bench_with_input(BenchmarkId::new("HashMap", order), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|k| hash.contains_key(k)).count())
+        });
+        let keys = self::keys(order, N_UNBALANCED);
+        let tree = build_unbalanced(&keys);
+        group.bench_with_input(BenchmarkId::new("BinTree (no rebalance)", order), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|k| tree.get_sorted_to_key_cmp(*k, |k| k, u64::partial_cmp).is_some()).count())
+        });
+    }
+    group.finish();
+}
+
+fn remove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("remove");
+    for order in ORDERS {
+        let keys = keys(order, N);
+        let map = build_map(&keys);
+        group.bench_with_input(BenchmarkId::new("BinTreeMap", order), &keys, |b, keys| {
+            b.iter_batched(|| map.clone(), |mut map| {
+                for k in keys { map.remove(k); }
+                map
+            }, criterion::BatchSize::LargeInput)
+        });
+        let set: BinTreeOrderedSet<u64> = keys.iter().copied().collect();
+        group.bench_with_input(BenchmarkId::new("BinTreeOrderedSet", order), &keys, |b, keys| {
+            b.iter_batched(|| set.clone(), |mut set| {
+                for k in keys { set.remove(k); }
+                set
+            }, criterion::BatchSize::LargeInput)
+        });
+        let btree: BTreeMap<u64, u64> = keys.iter().map(|&k| (k, k)).collect();
+        group.bench_with_input(BenchmarkId::new("BTreeMap", order), &keys, |b, keys| {
+            b.iter_batched(|| btree.clone(), |mut map| {
+                for k in keys { map.remove(k); }
+                map
+            }, criterion::BatchSize::LargeInput)
+        });
+        let hash: HashMap<u64, u64> = keys.iter().map(|&k| (k, k)).collect();
+        group.bench_with_input(BenchmarkId::new("HashMap", order), &keys, |b, keys| {
+            b.iter_batched(|| hash.clone(), |mut map| {
+                for k in keys { map.remove(k); }
+                map
+            }, criterion::BatchSize::LargeInput)
+        });
+        let keys = self::keys(order, N_UNBALANCED);
+        let tree = build_unbalanced(&keys);
+        group.bench_with_input(BenchmarkId::new("BinTree (no rebalance)", order), &keys, |b, keys| {
+            b.iter_batched(|| tree.clone(), |mut tree| {
+                for k in keys { tree.remove_sorted_to_key_cmp(k, |k| k, u64::partial_cmp, false); }
+                tree
+            }, criterion::BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+    for order in ORDERS {
+        let keys = keys(order, N);
+        let map = build_map(&keys);
+        group.bench_function(BenchmarkId::new("BinTreeMap", order), |b| b.iter(|| map.values().sum::<u64>()));
+        let set: BinTreeOrderedSet<u64> = keys.iter().copied().collect();
+        group.bench_function(BenchmarkId::new("BinTreeOrderedSet", order), |b| b.iter(|| set.iter().sum::<u64>()));
+        let btree: BTreeMap<u64, u64> = keys.iter().map(|&k| (k, k)).collect();
+        group.bench_function(BenchmarkId::new("BTreeMap", order), |b| b.iter(|| btree.values().sum::<u64>()));
+        let bset: BTreeSet<u64> = keys.iter().copied().collect();
+        group.bench_function(BenchmarkId::new("BTreeSet", order), |b| b.iter(|| bset.iter().sum::<u64>()));
+        let hash: HashMap<u64, u64> = keys.iter().map(|&k| (k, k)).collect();
+        group.bench_function(BenchmarkId::new("HashMap", order), |b| b.iter(|| hash.values().sum::<u64>()));
+        let tree = build_unbalanced(&self::keys(order, N_UNBALANCED));
+        group.bench_function(BenchmarkId::new("BinTree (no rebalance)", order), |b| b.iter(|| tree.iter().sum::<u64>()));
+    }
+    group.finish();
+}
+
+fn bulk_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk_build");
+    for order in ORDERS {
+        let keys = keys(order, N);
+        group.bench_with_input(BenchmarkId::new("BinTreeMap", order), &keys, |b, keys| {
+            b.iter(|| black_box(keys).iter().map(|&k| (k, k)).collect::<BinTreeMap<_, _>>())
+        });
+        group.bench_with_input(BenchmarkId::new("BinTreeOrderedSet", order), &keys, |b, keys| {
+            b.iter(|| black_box(keys).iter().copied().collect::<BinTreeOrderedSet<_>>())
+        });
+        group.bench_with_input(BenchmarkId::new("BinTree", order), &keys, |b, keys| {
+            b.iter(|| black_box(keys).iter().copied().collect::<BinTree<_>>())
+        });
+        group.bench_with_input(BenchmarkId::new("BTreeSet", order), &keys, |b, keys| {
+            b.iter(|| black_box(keys).iter().copied().collect::<BTreeSet<_>>())
+        });
+        group.bench_with_input(BenchmarkId::new("BTreeMap", order), &keys, |b, keys| {
+            b.iter(|| black_box(keys).iter().map(|&k| (k, k)).collect::<BTreeMap<_, _>>())
+        });
+        group.bench_with_input(BenchmarkId::new("HashMap", order), &keys, |b, keys| {
+            b.iter(|| black_box(keys).iter().map(|&k| (k, k)).collect::<HashMap<_, _>>())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, insert, lookup, remove, iterate, bulk_build);
+criterion_main!(benches);
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|ops: Vec<bin_tree_fuzz::SetOp>| {
+    bin_tree_fuzz::run_set_ops(ops);
+});
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|ops: Vec<bin_tree_fuzz::TreeOp>| {
+    bin_tree_fuzz::run_tree_ops(ops);
+});
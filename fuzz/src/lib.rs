@@ -0,0 +1,385 @@
+//! operation lists driven by the fuzz targets
+//! each run applies the operations to our containers and to a simple model,
+//! then checks results and tree invariants after every step (any mismatch panics)
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use arbitrary::Arbitrary;
+use bin_tree::{BinTree, BinTreeError, BinTreeMap, BinTreeOrderedSet, BinTreeVisitorMut, TreePath, TreeStep, VisitControl};
+
+/// operations on a BinTree: the first group keeps the tree sorted, the second doesn't
+#[derive(Arbitrary, Debug, Clone)]
+pub enum TreeOp {
+    Insert(u8),
+    InsertUnique(u8),
+    RemoveSorted(u8),
+    PopSorted,
+    PopMin,
+    PopMax,
+    PushLeft(u8),
+    PushRight(u8),
+    Remove(u8),
+    Pop,
+    PopLeft,
+    PopRight,
+    Swap(u8, u8),
+    GetMut(u8, u8),
+    MorrisAdd(u8),
+    IterStack,
+    IterMutStack(u8),
+    // path bits, path length, value
+    SubtreePushLeft(u8, u8, u8),
+    ReplaceSubtree(u8, u8, u8),
+    // threshold, number of items taken before the iterator is dropped
+    ExtractIf(u8, u8),
+    Retain(u8),
+    WalkMut(u8),
+    Prune(u8),
+}
+
+/// operations on a BinTreeMap
+#[derive(Arbitrary, Debug, Clone)]
+pub enum MapOp {
+    Insert(u8, u16),
+    Remove(u8),
+    Get(u8),
+    GetMut(u8, u16),
+    Swap(u8, u8),
+    PopFirst,
+    PopLast,
+    GetOrInsert(u8, u16),
+    ExtractIf(u8, u8),
+    Retain(u8),
+}
+
+/// operations on a BinTreeOrderedSet
+#[derive(Arbitrary, Debug, Clone)]
+pub enum SetOp {
+    Insert(u8),
+    Remove(u8),
+    Contains(u8),
+    PopFirst,
+    PopLast,
+    Take(u8),
+    Replace(u8),
+    ExtractIf(u8, u8),
+    Retain(u8),
+}
+
+/// heights must be exact and the tree must be balanced
+/// every mutation must keep the height fields up to date
+fn check_heights<T: Clone>(tree: &BinTree<T>) {
+    let mut copy = tree.clone();
+    assert!(!copy.recalculate_heights(), "stale heights");
+}
+
+fn check_balanced<T: Clone>(tree: &BinTree<T>) {
+    check_heights(tree);
+    assert!(tree.is_balanced(), "tree not balanced");
+}
+
+/// all traversals must visit the same items
+fn check_contents(tree: &BinTree<u8>, model: &[u8]) {
+    let mut expected = model.to_vec();
+    expected.sort_unstable();
+    for mut items in [
+        tree.iter_dfs_in().copied().collect::<Vec<_>>(),
+        tree.iter_dfs_pre().copied().collect(),
+        tree.iter_dfs_post().copied().collect(),
+        tree.iter_bfs().copied().collect(),
+    ] {
+        items.sort_unstable();
+        assert_eq!(items, expected);
+    }
+}
+
+/// removes one occurrence of value from an unsorted model
+fn model_take(model: &mut Vec<u8>, value: u8) -> Option<u8> {
+    let pos = model.iter().position(|v| *v == value)?;
+    Some(model.swap_remove(pos))
+}
+
+/// path of up to 7 steps (a set bit is a right step)
+fn path_from(bits: u8, len: u8) -> TreePath {
+    (0..len % 8).map(|i| if (bits >> i) & 1 == 1 { TreeStep::Right } else { TreeStep::Left }).collect()
+}
+
+/// the predicate of the retain operations
+fn keep(x: u8, m: u8) -> bool {
+    !(x ^ m).is_multiple_of(3)
+}
+
+/// the items left by prune (subtrees whose root is not kept are dropped)
+fn pruned(tree: &BinTree<u8>, keep: &impl Fn(&u8) -> bool, items: &mut Vec<u8>) {
+    if let Some((value, left, right)) = tree.node() {
+        if keep(value) {
+            items.push(*value);
+            pruned(left, keep, items);
+            pruned(right, keep, items);
+        }
+    }
+}
+
+/// adds to every value in walk_mut
+struct AddVisitor(u8);
+
+impl BinTreeVisitorMut<u8> for AddVisitor {
+    fn visit_value(&mut self, value: &mut u8, _depth: usize) -> VisitControl {
+        *value = value.wrapping_add(self.0);
+        VisitControl::Continue
+    }
+}
+
+/// expected result of BinTree::swap and BinTreeMap::swap
+fn expected_swap(found1: bool, found2: bool, same: bool) -> Result<(), BinTreeError> {
+    if !found1 {
+        Err(BinTreeError::SwapNotFound1)
+    } else if !found2 {
+        Err(BinTreeError::SwapNotFound2)
+    } else if same {
+        Err(BinTreeError::SwapSame)
+    } else {
+        Ok(())
+    }
+}
+
+pub fn run_tree_ops(ops: Vec<TreeOp>) {
+    let mut sorted = BinTree::new();
+    let mut sorted_model: Vec<u8> = vec![];
+    let mut unsorted = BinTree::new();
+    let mut unsorted_model: Vec<u8> = vec![];
+    for op in ops {
+        match op {
+            TreeOp::Insert(v) => {
+                sorted.insert(v);
+                let pos = sorted_model.partition_point(|x| *x <= v);
+                sorted_model.insert(pos, v);
+            }
+            TreeOp::InsertUnique(v) => {
+                let present = sorted_model.binary_search(&v).is_ok();
+                assert_eq!(sorted.insert_unique(v), !present);
+                if !present {
+                    let pos = sorted_model.partition_point(|x| *x < v);
+                    sorted_model.insert(pos, v);
+                }
+            }
+            TreeOp::RemoveSorted(v) => {
+                let expected = sorted_model.binary_search(&v).ok().map(|pos| sorted_model.remove(pos));
+                assert_eq!(sorted.remove_sorted(&v), expected);
+            }
+            TreeOp::PopSorted => {
+                let popped = sorted.pop_sorted(true);
+                assert_eq!(popped.is_some(), !sorted_model.is_empty());
+                if let Some(v) = popped {
+                    let pos = sorted_model.binary_search(&v).expect("popped value should be in the model");
+                    sorted_model.remove(pos);
+                }
+            }
+            TreeOp::PopMin => {
+                let expected = if sorted_model.is_empty() { None } else { Some(sorted_model.remove(0)) };
+                assert_eq!(sorted.pop_min_sorted(true), expected);
+            }
+            TreeOp::PopMax => assert_eq!(sorted.pop_max_sorted(true), sorted_model.pop()),
+            TreeOp::PushLeft(v) => {
+                unsorted.push_left(v);
+                unsorted_model.push(v);
+            }
+            TreeOp::PushRight(v) => {
+                unsorted.push_right(v);
+                unsorted_model.push(v);
+            }
+            TreeOp::Remove(v) => assert_eq!(unsorted.remove(&v), model_take(&mut unsorted_model, v)),
+            TreeOp::Pop | TreeOp::PopLeft | TreeOp::PopRight => {
+                let popped = match op {
+                    TreeOp::Pop => unsorted.pop(),
+                    TreeOp::PopLeft => unsorted.pop_left(),
+                    _ => unsorted.pop_right(),
+                };
+                assert_eq!(popped.is_some(), !unsorted_model.is_empty());
+                if let Some(v) = popped {
+                    model_take(&mut unsorted_model, v).expect("popped value should be in the model");
+                }
+            }
+            TreeOp::Swap(v1, v2) => {
+                let expected = expected_swap(unsorted_model.contains(&v1), unsorted_model.contains(&v2), v1 == v2);
+                assert_eq!(unsorted.swap(&v1, &v2), expected);
+            }
+            TreeOp::GetMut(v, new) => {
+                if let Some(value) = unsorted.get_mut(&v) {
+                    *value = new;
+                    model_take(&mut unsorted_model, v).expect("value should be in the model");
+                    unsorted_model.push(new);
+                } else {
+                    assert!(!unsorted_model.contains(&v));
+                }
+            }
+            TreeOp::MorrisAdd(d) => {
+                let in_order: Vec<u8> = unsorted.iter_dfs_in().copied().collect();
+                let mut visited = vec![];
+                unsorted.morris_for_each_mut(|x| {
+                    visited.push(*x);
+                    *x = x.wrapping_add(d);
+                });
+                assert_eq!(visited, in_order);
+                unsorted_model.iter_mut().for_each(|x| *x = x.wrapping_add(d));
+            }
+            TreeOp::IterStack => {
+                assert!(sorted.iter_stack().eq(sorted.iter_dfs_in()));
+                assert!(unsorted.iter_stack().eq(unsorted.iter_dfs_in()));
+            }
+            TreeOp::IterMutStack(d) => {
+                unsorted.iter_mut_stack().for_each(|x| *x ^= d);
+                unsorted_model.iter_mut().for_each(|x| *x ^= d);
+            }
+            TreeOp::SubtreePushLeft(bits, len, v) => {
+                if let Some(mut subtree) = unsorted.subtree_mut(&path_from(bits, len)) {
+                    subtree.push_left(v);
+                    unsorted_model.push(v);
+                }
+            }
+            TreeOp::ReplaceSubtree(bits, len, v) => {
+                if let Some(old) = unsorted.replace_subtree(&path_from(bits, len), BinTree::new_leaf(v)) {
+                    for x in old.iter() {
+                        model_take(&mut unsorted_model, *x).expect("replaced value should be in the model");
+                    }
+                    unsorted_model.push(v);
+                }
+            }
+            TreeOp::ExtractIf(threshold, count) => {
+                let in_order: Vec<u8> = unsorted.iter_dfs_in().copied().collect();
+                let count = usize::from(count % 8);
+                let extracted: Vec<u8> = unsorted.extract_if(|x| *x < threshold).take(count).collect();
+                let expected: Vec<u8> = in_order.iter().copied().filter(|x| *x < threshold).take(count).collect();
+                assert_eq!(extracted, expected);
+                // the other items keep their in-order sequence
+                let mut pending = extracted.len();
+                let remaining = in_order.iter().filter(|x| {
+                    let taken = **x < threshold && pending > 0;
+                    if taken { pending -= 1 }
+                    !taken
+                });
+                assert!(unsorted.iter_dfs_in().eq(remaining));
+                for x in extracted {
+                    model_take(&mut unsorted_model, x).expect("extracted value should be in the model");
+                }
+            }
+            TreeOp::Retain(m) => {
+                let in_order: Vec<u8> = unsorted.iter_dfs_in().copied().filter(|x| keep(*x, m)).collect();
+                unsorted.retain(|x| keep(*x, m));
+                assert!(unsorted.iter_dfs_in().eq(in_order.iter()));
+                unsorted_model.retain(|x| keep(*x, m));
+            }
+            TreeOp::WalkMut(d) => {
+                assert_eq!(unsorted.walk_mut(&mut AddVisitor(d)), VisitControl::Continue);
+                unsorted_model.iter_mut().for_each(|x| *x = x.wrapping_add(d));
+            }
+            TreeOp::Prune(t) => {
+                unsorted_model.clear();
+                pruned(&unsorted, &|x| *x != t, &mut unsorted_model);
+                unsorted.prune(|x| *x != t);
+            }
+        }
+        assert!(sorted.iter().eq(sorted_model.iter()));
+        assert_eq!(sorted.len(), sorted_model.len());
+        check_balanced(&sorted);
+        assert_eq!(unsorted.len(), unsorted_model.len());
+        check_heights(&unsorted);
+        check_contents(&unsorted, &unsorted_model);
+    }
+}
+
+pub fn run_map_ops(ops: Vec<MapOp>) {
+    let mut map = BinTreeMap::new();
+    let mut model = BTreeMap::new();
+    for op in ops {
+        match op {
+            MapOp::Insert(k, v) => {
+                map.insert(k, v);
+                model.insert(k, v);
+            }
+            MapOp::Remove(k) => assert_eq!(map.remove(&k), model.remove(&k)),
+            MapOp::Get(k) => assert_eq!(map.get(&k), model.get(&k)),
+            MapOp::GetMut(k, v) => {
+                if let Some(value) = map.get_mut(&k) { *value = v; }
+                if let Some(value) = model.get_mut(&k) { *value = v; }
+            }
+            MapOp::Swap(k1, k2) => {
+                let expected = expected_swap(model.contains_key(&k1), model.contains_key(&k2), k1 == k2);
+                assert_eq!(map.swap(&k1, &k2), expected);
+                if expected.is_ok() {
+                    let v1 = model[&k1];
+                    let v2 = model.insert(k2, v1).expect("key should be in the model");
+                    model.insert(k1, v2);
+                }
+            }
+            MapOp::PopFirst => assert_eq!(map.pop_first(), model.pop_first()),
+            MapOp::PopLast => assert_eq!(map.pop_last(), model.pop_last()),
+            MapOp::GetOrInsert(k, v) => {
+                let value = map.get_or_insert_with(k, || v);
+                let expected = model.entry(k).or_insert(v);
+                assert_eq!(value, expected);
+                *value = value.wrapping_add(1);
+                *expected = expected.wrapping_add(1);
+            }
+            MapOp::ExtractIf(threshold, count) => {
+                let count = usize::from(count % 8);
+                let extracted: Vec<(u8, u16)> = map.extract_if(|k, _| *k < threshold).take(count).collect();
+                let expected: Vec<(u8, u16)> = model.range(..threshold).take(count).map(|(k, v)| (*k, *v)).collect();
+                assert_eq!(extracted, expected);
+                for (k, _) in extracted {
+                    model.remove(&k);
+                }
+            }
+            MapOp::Retain(m) => {
+                map.retain(|k, v| {
+                    *v = v.wrapping_add(1);
+                    keep(*k, m)
+                });
+                model.retain(|k, v| {
+                    *v = v.wrapping_add(1);
+                    keep(*k, m)
+                });
+            }
+        }
+        assert_eq!(map.len(), model.len());
+        assert_eq!(map.iter().len(), model.len());
+        assert!(map.iter().eq(model.iter()));
+        check_balanced(map.inner());
+    }
+}
+
+pub fn run_set_ops(ops: Vec<SetOp>) {
+    let mut set = BinTreeOrderedSet::new();
+    let mut model = BTreeSet::new();
+    for op in ops {
+        match op {
+            SetOp::Insert(v) => {
+                set.insert(v);
+                model.insert(v);
+            }
+            SetOp::Remove(v) => assert_eq!(set.remove(&v), model.take(&v)),
+            SetOp::Contains(v) => assert_eq!(set.contains(&v), model.contains(&v)),
+            SetOp::PopFirst => assert_eq!(set.pop_first(), model.pop_first()),
+            SetOp::PopLast => assert_eq!(set.pop_last(), model.pop_last()),
+            SetOp::Take(v) => assert_eq!(set.take(&v), model.take(&v)),
+            SetOp::Replace(v) => assert_eq!(set.replace(v), model.replace(v)),
+            SetOp::ExtractIf(threshold, count) => {
+                let count = usize::from(count % 8);
+                let extracted: Vec<u8> = set.extract_if(|x| *x < threshold).take(count).collect();
+                let expected: Vec<u8> = model.range(..threshold).take(count).copied().collect();
+                assert_eq!(extracted, expected);
+                for x in extracted {
+                    model.remove(&x);
+                }
+            }
+            SetOp::Retain(m) => {
+                set.retain(|x| keep(*x, m));
+                model.retain(|x| keep(*x, m));
+            }
+        }
+        assert_eq!(set.len(), model.len());
+        assert_eq!(set.iter().len(), model.len());
+        assert!(set.iter().eq(model.iter()));
+        check_balanced(set.inner());
+    }
+}
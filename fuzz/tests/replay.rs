@@ -0,0 +1,73 @@
+// replays the fuzz corpora (and a few pseudo-random inputs) through the same code as the fuzz targets
+// this is mostly useful under Miri, which can't run libFuzzer itself:
+// MIRIFLAGS=-Zmiri-disable-isolation cargo +nightly miri test
+
+use std::path::Path;
+
+use arbitrary::{Arbitrary, Unstructured};
+use bin_tree_fuzz::{run_map_ops, run_set_ops, run_tree_ops};
+
+/// pseudo-random inputs (xorshift) so that the replay does something even without a corpus
+fn seed_inputs() -> Vec<Vec<u8>> {
+    [0x9e37_79b9_7f4a_7c15u64, 0x2545_f491_4f6c_dd1d, 0xdead_beef, 1].iter()
+        .map(|&seed| {
+            let mut state = seed;
+            (0..2048).map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 32) as u8
+            }).collect()
+        })
+        .collect()
+}
+
+/// ops read one after the other until the seed is used up
+/// (the collection format of arbitrary_take_rest stops at the first even continuation byte)
+fn seed_ops<'a, Op: Arbitrary<'a>>(data: &'a [u8]) -> Vec<Op> {
+    let mut u = Unstructured::new(data);
+    let mut ops = vec![];
+    while !u.is_empty() {
+        match Op::arbitrary(&mut u) {
+            Ok(op) => ops.push(op),
+            Err(_) => break,
+        }
+    }
+    ops
+}
+
+/// inputs found in corpus/<target> and artifacts/<target> (when present)
+fn saved_inputs(target: &str) -> Vec<Vec<u8>> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    ["corpus", "artifacts"].iter()
+        .filter_map(|dir| std::fs::read_dir(root.join(dir).join(target)).ok())
+        .flatten()
+        .filter_map(|entry| std::fs::read(entry.ok()?.path()).ok())
+        .collect()
+}
+
+fn replay<'a, Op: Arbitrary<'a>>(seeds: &'a [Vec<u8>], saved: &'a [Vec<u8>], run: fn(Vec<Op>)) {
+    for data in seeds {
+        run(seed_ops(data));
+    }
+    for data in saved {
+        if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(Unstructured::new(data)) {
+            run(ops);
+        }
+    }
+}
+
+#[test]
+fn replay_tree_ops() {
+    replay(&seed_inputs(), &saved_inputs("tree_ops"), run_tree_ops);
+}
+
+#[test]
+fn replay_map_ops() {
+    replay(&seed_inputs(), &saved_inputs("map_ops"), run_map_ops);
+}
+
+#[test]
+fn replay_set_ops() {
+    replay(&seed_inputs(), &saved_inputs("set_ops"), run_set_ops);
+}
@@ -0,0 +1,56 @@
+use crate::BinTree;
+
+impl<Item> BinTree<Item> {
+    /// lowest common ancestor of 2 values in a sorted tree, O(height)
+    /// a node is its own ancestor, None if a value is not in the tree
+    pub fn lowest_common_ancestor_sorted(&self, a : &Item, b : &Item) -> Option<&Item> where Item : PartialOrd {
+        let mut tree = self;
+        while let Some((value, left, right)) = tree.node() {
+            if a < value && b < value {
+                tree = left;
+            } else if a > value && b > value {
+                tree = right;
+            } else {
+                return (tree.contains_sorted(a) && tree.contains_sorted(b)).then_some(value);
+            }
+        }
+        None
+    }
+    /// lowest common ancestor of 2 values (no ordering assumed, uses the shallowest matches)
+    /// a node is its own ancestor, None if a value is not in the tree
+    pub fn lowest_common_ancestor(&self, a : &Item, b : &Item) -> Option<&Item> where Item : PartialEq {
+        let common = self.path_to(a)?.common_prefix(&self.path_to(b)?);
+        self.subtree(&common)?.value()
+    }
+    /// depth of a value in a sorted tree (0 for the root), O(height)
+    pub fn depth_of_sorted(&self, a : &Item) -> Option<usize> where Item : PartialOrd {
+        let mut tree = self;
+        let mut depth = 0;
+        while let Some((value, left, right)) = tree.node() {
+            if a < value {
+                tree = left;
+            } else if a > value {
+                tree = right;
+            } else {
+                return Some(depth);
+            }
+            depth += 1;
+        }
+        None
+    }
+    /// depth of a value (0 for the root, no ordering assumed, uses the shallowest match)
+    pub fn depth_of(&self, a : &Item) -> Option<usize> where Item : PartialEq {
+        self.path_to(a).map(|path| path.len())
+    }
+    /// number of edges between 2 values in a sorted tree, O(height)
+    pub fn distance_sorted(&self, a : &Item, b : &Item) -> Option<usize> where Item : PartialOrd {
+        let ancestor = self.lowest_common_ancestor_sorted(a, b)?;
+        Some(self.depth_of_sorted(a)? + self.depth_of_sorted(b)? - 2 * self.depth_of_sorted(ancestor)?)
+    }
+    /// number of edges between 2 values (no ordering assumed, uses the shallowest matches)
+    pub fn distance(&self, a : &Item, b : &Item) -> Option<usize> where Item : PartialEq {
+        let path_a = self.path_to(a)?;
+        let path_b = self.path_to(b)?;
+        Some(path_a.len() + path_b.len() - 2 * path_a.common_prefix(&path_b).len())
+    }
+}
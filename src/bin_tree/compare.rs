@@ -0,0 +1,35 @@
+use crate::BinTree;
+
+impl<Item> BinTree<Item> {
+    /// same shape and equal values at the same positions (the stored heights are ignored)
+    pub fn shape_eq(&self, other : &Self) -> bool where Item : PartialEq {
+        match (self.node(), other.node()) {
+            (None, None) => true,
+            (Some((value1, left1, right1)), Some((value2, left2, right2))) =>
+                value1 == value2 && left1.shape_eq(left2) && right1.shape_eq(right2),
+            _ => false,
+        }
+    }
+    /// same values in the same in-order sequence (the shapes may differ)
+    pub fn content_eq(&self, other : &Self) -> bool where Item : PartialEq {
+        self.iter_stack().eq(other.iter_stack())
+    }
+    /// same shape (the values are ignored)
+    pub fn same_shape<U>(&self, other : &BinTree<U>) -> bool {
+        match (self.node(), other.node()) {
+            (None, None) => true,
+            (Some((_, left1, right1)), Some((_, left2, right2))) =>
+                left1.same_shape(left2) && right1.same_shape(right2),
+            _ => false,
+        }
+    }
+}
+
+impl<Item : PartialEq> PartialEq for BinTree<Item> {
+    /// structural equality (see shape_eq)
+    fn eq(&self, other : &Self) -> bool {
+        self.shape_eq(other)
+    }
+}
+
+impl<Item : Eq> Eq for BinTree<Item> {}
@@ -1,5 +1,5 @@
 use crate::BinTree;
-use std::ops::Deref;
+use core::ops::Deref;
 
 /// different kinds of formatting for the tree
 pub enum FormattedBinTreeType<'a> {
@@ -13,9 +13,9 @@ pub struct FormattedBinTree<'a, T> {
     format: FormattedBinTreeType<'a>,
 }
 
-impl<'a,T : std::fmt::Debug> std::fmt::Display for FormattedBinTree<'a,T> {
+impl<'a,T : core::fmt::Debug> core::fmt::Display for FormattedBinTree<'a,T> {
     /// display a formatted tree according to the internal format field
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self.format {
             FormattedBinTreeType::Line => self.inner.write_line(f),
             FormattedBinTreeType::PrettyIndent(tab) => self.inner.pretty_write(f,tab),
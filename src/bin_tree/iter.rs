@@ -1,6 +1,7 @@
-use std::{collections::VecDeque};
+use alloc::{collections::VecDeque, vec, vec::Vec};
 
-use crate::BinTree;
+use crate::{BinTree, TreePath, TreeStep};
+use core::iter::FusedIterator;
 
 /// tree traversal methods: depth-first (3 orders), breadth-first
 enum BinTreeTraversal {
@@ -9,6 +10,7 @@ enum BinTreeTraversal {
 }
 
 /// 3 orders for depth-first: in order, pre-order, post-order
+#[allow(clippy::enum_variant_names)]
 enum DepthFirstOrder {
     InOrder,
     PreOrder,
@@ -31,6 +33,8 @@ enum IterIntoData<Item> {
 pub struct BinTreeIntoIter<T> {
     data: VecDeque<IterIntoData<T>>,
     traversal: BinTreeTraversal,
+    /// values and non-empty trees in the deque (each gives at least one item)
+    pending: usize,
 }
 
 impl<T> IntoIterator for BinTree<T> {
@@ -46,31 +50,49 @@ impl<T> IntoIterator for BinTree<T> {
 impl<T> BinTree<T> {
     fn into_iter_traversal(self, traversal : BinTreeTraversal) -> BinTreeIntoIter<T> {
         BinTreeIntoIter { 
+            pending: usize::from(!self.is_empty()),
             data: VecDeque::from(vec![IterIntoData::Tree(self)]),
             traversal,
         }
     }
     /// depth-first in-order iterator
+    /// memory: a deque of O(height) entries
     pub fn into_iter_dfs_in(self) -> BinTreeIntoIter<T> {
         self.into_iter_traversal(DepthFirst(InOrder))
     }
     /// depth-first pre-order iterator
+    /// memory: a deque of O(height) entries
     pub fn into_iter_dfs_pre(self) -> BinTreeIntoIter<T> {
         self.into_iter_traversal(DepthFirst(PreOrder))
     }
     /// depth-first post-order iterator
+    /// memory: a deque of O(height) entries
     pub fn into_iter_dfs_post(self) -> BinTreeIntoIter<T> {
         self.into_iter_traversal(DepthFirst(PostOrder))
     }
     /// breadth-first iterator
+    /// memory: a deque of O(width) entries (up to half of the items)
     pub fn into_iter_bfs(self) -> BinTreeIntoIter<T> {
         self.into_iter_traversal(BreadthFirst)
     }
 }
 
+impl<T> BinTreeIntoIter<T> {
+    /// adds the number of remaining items (known by maps and sets)
+    pub(crate) fn with_len(self, len : usize) -> BinTreeExactSizeIter<Self> {
+        BinTreeExactSizeIter { iter: self, len }
+    }
+}
+
+impl<T> FusedIterator for BinTreeIntoIter<T> {}
+
 impl<T> Iterator for BinTreeIntoIter<T> {
     type Item = T;
 
+    /// at least the pending values and non-empty trees (heights can be stale, so no upper bound)
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pending, None)
+    }
     /// a deque is used to push and pop from both ends according to the specified traversal behavior
     fn next(&mut self) -> Option<Self::Item> {
         let pop = match self.traversal {
@@ -80,12 +102,16 @@ impl<T> Iterator for BinTreeIntoIter<T> {
         use IterIntoData::*;
         match pop {
             None => None, // no more work
-            Some(Value(item)) => Some(item),
+            Some(Value(item)) => {
+                self.pending -= 1;
+                Some(item)
+            },
             Some(Tree(tree)) => {
                 if tree.is_empty() {
                     self.next()
                 } else {
                     let (value,left,right) = tree.into_node().expect("tree should not be empty");
+                    self.pending += usize::from(!left.is_empty()) + usize::from(!right.is_empty());
                     match self.traversal {
                         DepthFirst(InOrder) => {
                             self.data.push_back(Tree(right));
@@ -133,6 +159,8 @@ enum IterData<'a, Item> {
 pub struct BinTreeIter<'a, T> {
     data: VecDeque<IterData<'a,T>>,
     traversal: BinTreeTraversal,
+    /// values and non-empty trees in the deque (each gives at least one item)
+    pending: usize,
 }
 
 impl<'a, T> IntoIterator for &'a BinTree<T> {
@@ -152,31 +180,49 @@ impl<'a, T> BinTree<T> {
     }
     fn iter_traversal(&'a self, traversal : BinTreeTraversal) -> BinTreeIter<'a, T> {
         BinTreeIter { 
+            pending: usize::from(!self.is_empty()),
             data: VecDeque::from(vec![IterData::Tree(self)]),
             traversal,
         }
     }
     /// depth-first in-order iterator
+    /// memory: a deque of O(height) entries
     pub fn iter_dfs_in(&'a self) -> BinTreeIter<'a, T> {
         self.iter_traversal(DepthFirst(InOrder))
     }
     /// depth-first pre-order iterator
+    /// memory: a deque of O(height) entries
     pub fn iter_dfs_pre(&'a self) -> BinTreeIter<'a, T> {
         self.iter_traversal(DepthFirst(PreOrder))
     }
     /// depth-first post-order iterator
+    /// memory: a deque of O(height) entries
     pub fn iter_dfs_post(&'a self) -> BinTreeIter<'a, T> {
         self.iter_traversal(DepthFirst(PostOrder))
     }
     /// breadth-first iterator
+    /// memory: a deque of O(width) entries (up to half of the items)
     pub fn iter_bfs(&'a self) -> BinTreeIter<'a, T> {
         self.iter_traversal(BreadthFirst)
     }
 }
 
+impl<'a, T> BinTreeIter<'a, T> {
+    /// adds the number of remaining items (known by maps and sets)
+    pub(crate) fn with_len(self, len : usize) -> BinTreeExactSizeIter<Self> {
+        BinTreeExactSizeIter { iter: self, len }
+    }
+}
+
+impl<'a, T> FusedIterator for BinTreeIter<'a, T> {}
+
 impl<'a,T> Iterator for BinTreeIter<'a,T> {
     type Item = &'a T;
 
+    /// at least the pending values and non-empty trees (heights can be stale, so no upper bound)
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pending, None)
+    }
     /// a deque is used to push and pop from both ends according to the specified traversal behavior
     fn next(&mut self) -> Option<Self::Item> {
         let pop = match self.traversal {
@@ -186,12 +232,16 @@ impl<'a,T> Iterator for BinTreeIter<'a,T> {
         use IterData::*;
         match pop {
             None => None, // no more work
-            Some(Value(item)) => Some(item),
+            Some(Value(item)) => {
+                self.pending -= 1;
+                Some(item)
+            },
             Some(Tree(tree)) => {
                 if tree.is_empty() {
                     self.next()
                 } else {
                     let (value,left,right) = tree.node().expect("tree should not be empty");
+                    self.pending += usize::from(!left.is_empty()) + usize::from(!right.is_empty());
                     match self.traversal {
                         DepthFirst(InOrder) => {
                             self.data.push_back(Tree(right));
@@ -239,6 +289,8 @@ enum IterMutData<'a, Item> {
 pub struct BinTreeIterMut<'a, T> {
     data: VecDeque<IterMutData<'a,T>>,
     traversal: BinTreeTraversal,
+    /// values and non-empty trees in the deque (each gives at least one item)
+    pending: usize,
 }
 
 impl<'a, T> IntoIterator for &'a mut BinTree<T> {
@@ -258,31 +310,49 @@ impl<'a, T> BinTree<T> {
     }
     fn iter_mut_traversal(&'a mut self, traversal : BinTreeTraversal) -> BinTreeIterMut<'a, T> {
         BinTreeIterMut { 
+            pending: usize::from(!self.is_empty()),
             data: VecDeque::from(vec![IterMutData::Tree(self)]),
             traversal,
         }
     }
     /// depth-first in-order iterator
+    /// memory: a deque of O(height) entries
     pub fn iter_mut_dfs_in(&'a mut self) -> BinTreeIterMut<'a, T> {
         self.iter_mut_traversal(DepthFirst(InOrder))
     }
     /// depth-first pre-order iterator
+    /// memory: a deque of O(height) entries
     pub fn iter_mut_dfs_pre(&'a mut self) -> BinTreeIterMut<'a, T> {
         self.iter_mut_traversal(DepthFirst(PreOrder))
     }
     /// depth-first post-order iterator
+    /// memory: a deque of O(height) entries
     pub fn iter_mut_dfs_post(&'a mut self) -> BinTreeIterMut<'a, T> {
         self.iter_mut_traversal(DepthFirst(PostOrder))
     }
     /// breadth-first iterator
+    /// memory: a deque of O(width) entries (up to half of the items)
     pub fn iter_mut_bfs(&'a mut self) -> BinTreeIterMut<'a, T> {
         self.iter_mut_traversal(BreadthFirst)
     }
 }
 
+impl<'a, T> BinTreeIterMut<'a, T> {
+    /// adds the number of remaining items (known by maps and sets)
+    pub(crate) fn with_len(self, len : usize) -> BinTreeExactSizeIter<Self> {
+        BinTreeExactSizeIter { iter: self, len }
+    }
+}
+
+impl<'a, T> FusedIterator for BinTreeIterMut<'a, T> {}
+
 impl<'a,T> Iterator for BinTreeIterMut<'a,T> {
     type Item = &'a mut T;
 
+    /// at least the pending values and non-empty trees (heights can be stale, so no upper bound)
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pending, None)
+    }
     /// a deque is used to push and pop from both ends according to the specified traversal behavior
     fn next(&mut self) -> Option<Self::Item> {
         let pop = match self.traversal {
@@ -292,12 +362,16 @@ impl<'a,T> Iterator for BinTreeIterMut<'a,T> {
         use IterMutData::*;
         match pop {
             None => None, // no more work
-            Some(Value(item)) => Some(item),
+            Some(Value(item)) => {
+                self.pending -= 1;
+                Some(item)
+            },
             Some(Tree(tree)) => {
                 if tree.is_empty() {
                     self.next()
                 } else {
                     let (value,left,right) = tree.node_mut().expect("tree should not be empty");
+                    self.pending += usize::from(!left.is_empty()) + usize::from(!right.is_empty());
                     match self.traversal {
                         DepthFirst(InOrder) => {
                             self.data.push_back(Tree(right));
@@ -330,3 +404,163 @@ impl<'a,T> Iterator for BinTreeIterMut<'a,T> {
         }
     }
 }
+
+//
+// iter with depth and path
+//
+
+/// Value or Tree (with depth and path)
+enum IterPathData<'a, Item> {
+    Value(usize, TreePath, &'a Item),
+    Tree(usize, TreePath, &'a BinTree<Item>)
+}
+
+/// iterator struct using a deque, yields (depth, path, value)
+pub struct BinTreePathIter<'a, T> {
+    data: VecDeque<IterPathData<'a,T>>,
+    traversal: BinTreeTraversal,
+}
+
+impl<'a, T> BinTree<T> {
+    fn iter_path_traversal(&'a self, traversal : BinTreeTraversal) -> BinTreePathIter<'a, T> {
+        BinTreePathIter { 
+            data: VecDeque::from(vec![IterPathData::Tree(0, TreePath::new(), self)]),
+            traversal,
+        }
+    }
+    /// depth-first in-order iterator with depth and path
+    /// memory: a deque of O(height) entries, each with a path of O(height) steps
+    pub fn iter_dfs_in_with_path(&'a self) -> BinTreePathIter<'a, T> {
+        self.iter_path_traversal(DepthFirst(InOrder))
+    }
+    /// depth-first pre-order iterator with depth and path
+    /// memory: a deque of O(height) entries, each with a path of O(height) steps
+    pub fn iter_dfs_pre_with_path(&'a self) -> BinTreePathIter<'a, T> {
+        self.iter_path_traversal(DepthFirst(PreOrder))
+    }
+    /// depth-first post-order iterator with depth and path
+    /// memory: a deque of O(height) entries, each with a path of O(height) steps
+    pub fn iter_dfs_post_with_path(&'a self) -> BinTreePathIter<'a, T> {
+        self.iter_path_traversal(DepthFirst(PostOrder))
+    }
+    /// breadth-first iterator with depth and path
+    /// memory: a deque of O(width) entries, each with a path of O(height) steps
+    pub fn iter_bfs_with_path(&'a self) -> BinTreePathIter<'a, T> {
+        self.iter_path_traversal(BreadthFirst)
+    }
+    /// iterator over the levels of the tree (breadth-first), one vec of values per level
+    /// memory: O(width) for the current level
+    pub fn levels(&'a self) -> BinTreeLevels<'a, T> {
+        BinTreeLevels { level: if self.is_empty() { vec![] } else { vec![self] } }
+    }
+}
+
+impl<'a,T> Iterator for BinTreePathIter<'a,T> {
+    type Item = (usize, TreePath, &'a T);
+
+    /// a deque is used to push and pop from both ends according to the specified traversal behavior
+    fn next(&mut self) -> Option<Self::Item> {
+        let pop = match self.traversal {
+            DepthFirst(_) => self.data.pop_back(),
+            BreadthFirst => self.data.pop_front(),
+        };
+        use IterPathData::*;
+        match pop {
+            None => None, // no more work
+            Some(Value(depth, path, item)) => Some((depth, path, item)),
+            Some(Tree(depth, path, tree)) => {
+                if tree.is_empty() {
+                    self.next()
+                } else {
+                    let (value,left,right) = tree.node().expect("tree should not be empty");
+                    let left = Tree(depth + 1, path.child(TreeStep::Left), left);
+                    let right = Tree(depth + 1, path.child(TreeStep::Right), right);
+                    let value = Value(depth, path, value);
+                    match self.traversal {
+                        DepthFirst(InOrder) => {
+                            self.data.push_back(right);
+                            self.data.push_back(value);
+                            self.data.push_back(left);
+                            self.next()
+                        },
+                        DepthFirst(PreOrder) => {
+                            self.data.push_back(right);
+                            self.data.push_back(left);
+                            self.data.push_back(value);
+                            self.next()
+                        },
+                        DepthFirst(PostOrder) => {
+                            self.data.push_back(value);
+                            self.data.push_back(right);
+                            self.data.push_back(left);
+                            self.next()
+                        },
+                        BreadthFirst => {
+                            self.data.push_back(value);
+                            self.data.push_back(left);
+                            self.data.push_back(right);
+                            self.next()
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+//
+// levels
+//
+
+/// iterator over the levels of a tree (keeps the non-empty trees of the current level)
+pub struct BinTreeLevels<'a, T> {
+    level: Vec<&'a BinTree<T>>,
+}
+
+impl<'a,T> Iterator for BinTreeLevels<'a,T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.level.is_empty() {
+            None
+        } else {
+            let mut values = Vec::with_capacity(self.level.len());
+            let mut next_level = vec![];
+            for tree in self.level.drain(..) {
+                let (value,left,right) = tree.node().expect("levels only keep non-empty trees");
+                values.push(value);
+                next_level.extend([left, right].into_iter().filter(|t| !t.is_empty()));
+            }
+            self.level = next_level;
+            Some(values)
+        }
+    }
+}
+
+//
+// with_len
+//
+
+/// iterator with a known number of remaining items (iterators of maps and sets)
+pub struct BinTreeExactSizeIter<I> {
+    iter: I,
+    len: usize,
+}
+
+impl<I : Iterator> Iterator for BinTreeExactSizeIter<I> {
+    type Item = I::Item;
+
+    /// exact (the map or set knows its length)
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        self.len -= 1;
+        Some(item)
+    }
+}
+
+impl<I : Iterator> ExactSizeIterator for BinTreeExactSizeIter<I> {}
+
+impl<I : FusedIterator> FusedIterator for BinTreeExactSizeIter<I> {}
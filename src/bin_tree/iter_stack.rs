@@ -0,0 +1,181 @@
+use alloc::vec::Vec;
+use crate::BinTree;
+
+/// initial capacity of the stack (the maintained height, so the stack never grows)
+fn stack_capacity<T>(tree : &BinTree<T>) -> usize {
+    usize::try_from(tree.height()).unwrap_or(0)
+}
+
+//
+// iter
+//
+
+/// in-order iterator using a stack of the pending ancestors (at most height entries)
+pub struct BinTreeStackIter<'a, T> {
+    stack : Vec<(&'a T, &'a BinTree<T>)>,
+}
+
+impl<'a, T> BinTreeStackIter<'a, T> {
+    /// pushes the left spine of a tree
+    fn push_left(&mut self, mut tree : &'a BinTree<T>) {
+        while let Some((value, left, right)) = tree.node() {
+            self.stack.push((value, right));
+            tree = left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for BinTreeStackIter<'a, T> {
+    type Item = &'a T;
+    /// pops the next ancestor and pushes the left spine of its right subtree
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, right) = self.stack.pop()?;
+        self.push_left(right);
+        Some(value)
+    }
+}
+
+//
+// iter_mut
+//
+
+/// in-order mutable iterator using a stack of the pending ancestors (at most height entries)
+pub struct BinTreeStackIterMut<'a, T> {
+    stack : Vec<(&'a mut T, &'a mut BinTree<T>)>,
+}
+
+impl<'a, T> BinTreeStackIterMut<'a, T> {
+    /// pushes the left spine of a tree
+    fn push_left(&mut self, mut tree : &'a mut BinTree<T>) {
+        while let Some((value, left, right)) = tree.node_mut() {
+            self.stack.push((value, right));
+            tree = left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for BinTreeStackIterMut<'a, T> {
+    type Item = &'a mut T;
+    /// pops the next ancestor and pushes the left spine of its right subtree
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, right) = self.stack.pop()?;
+        self.push_left(right);
+        Some(value)
+    }
+}
+
+impl<T> BinTree<T> {
+    /// depth-first in-order iterator with O(height) memory
+    /// the stack is allocated once with the height of the tree as capacity
+    pub fn iter_stack(&self) -> BinTreeStackIter<'_, T> {
+        let mut iter = BinTreeStackIter { stack : Vec::with_capacity(stack_capacity(self)) };
+        iter.push_left(self);
+        iter
+    }
+    /// depth-first in-order mutable iterator with O(height) memory
+    /// the stack is allocated once with the height of the tree as capacity
+    pub fn iter_mut_stack(&mut self) -> BinTreeStackIterMut<'_, T> {
+        let mut iter = BinTreeStackIterMut { stack : Vec::with_capacity(stack_capacity(self)) };
+        iter.push_left(self);
+        iter
+    }
+}
+
+//
+// morris_for_each_mut
+//
+
+/// directions from the root to the walked tree, one bit per level (set for a right branch)
+struct PathBits {
+    words : Vec<u64>,
+    len : usize,
+}
+
+impl PathBits {
+    fn push(&mut self, right : bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        if right {
+            *self.words.last_mut().expect("word pushed") |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+    }
+    fn pop(&mut self) -> Option<bool> {
+        self.len = self.len.checked_sub(1)?;
+        let bit = self.len % 64;
+        let word = self.words.last_mut().expect("one word per 64 bits");
+        let right = (*word >> bit) & 1 == 1;
+        *word &= !(1 << bit);
+        if bit == 0 {
+            self.words.pop();
+        }
+        Some(right)
+    }
+}
+
+/// state of a link-inversion traversal: the walked tree is taken out of its parent,
+/// and the branch it leaves empty holds the link to the grandparent instead
+/// (so each ancestor is reachable from its child, and the tree is whole again when walking up)
+struct InvertedWalk<'a, T> {
+    tree : &'a mut BinTree<T>,
+    cur : BinTree<T>,
+    parent : BinTree<T>,
+    path : PathBits,
+}
+
+impl<T> InvertedWalk<'_, T> {
+    /// walks down the left branches until an empty tree
+    fn descend(&mut self) {
+        while let Some(left) = self.cur.left_mut() {
+            let left = core::mem::replace(left, core::mem::take(&mut self.parent));
+            self.parent = core::mem::replace(&mut self.cur, left);
+            self.path.push(false);
+        }
+    }
+    /// walks up to the next ancestor to visit (its right branch becomes the walked tree)
+    /// returns false when the whole tree has been walked
+    fn ascend(&mut self) -> bool {
+        while let Some(right) = self.path.pop() {
+            if right {
+                let grandparent = core::mem::replace(self.parent.right_mut().expect("parent is not empty"), core::mem::take(&mut self.cur));
+                self.cur = core::mem::replace(&mut self.parent, grandparent);
+            } else {
+                let grandparent = core::mem::replace(self.parent.left_mut().expect("parent is not empty"), core::mem::take(&mut self.cur));
+                self.cur = core::mem::replace(self.parent.right_mut().expect("parent is not empty"), grandparent);
+                self.path.push(true);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<T> Drop for InvertedWalk<'_, T> {
+    /// restores the links on the way up (the walk is done, or f panicked)
+    fn drop(&mut self) {
+        while let Some(right) = self.path.pop() {
+            let parent = if right { self.parent.right_mut() } else { self.parent.left_mut() };
+            let grandparent = core::mem::replace(parent.expect("parent is not empty"), core::mem::take(&mut self.cur));
+            self.cur = core::mem::replace(&mut self.parent, grandparent);
+        }
+        *self.tree = core::mem::take(&mut self.cur);
+    }
+}
+
+impl<T> BinTree<T> {
+    /// depth-first in-order traversal of the values without a stack of references (Morris-style)
+    /// instead of threads, the links on the current path are inverted (Deutsch-Schorr-Waite) and
+    /// restored on the way up, even when f panics; subtrees are moved, nothing is reallocated
+    /// memory: one bit per level of the current path
+    pub fn morris_for_each_mut(&mut self, mut f : impl FnMut(&mut T)) {
+        let words = stack_capacity(self).div_ceil(64);
+        let cur = core::mem::take(self);
+        let mut walk = InvertedWalk { tree : self, cur, parent : BinTree::new(), path : PathBits { words : Vec::with_capacity(words), len : 0 } };
+        walk.descend();
+        while walk.ascend() {
+            f(walk.parent.value_mut().expect("visited tree is not empty"));
+            walk.descend();
+        }
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(feature = "merkle")]
+use crate::BinTree;
+
+/// cached hash of a subtree (0 means not computed)
+/// a zero-sized no-op when the merkle feature is disabled
+#[cfg(feature = "merkle")]
+pub(crate) struct HashCache(core::sync::atomic::AtomicU64);
+
+#[cfg(feature = "merkle")]
+impl HashCache {
+    pub(crate) fn new() -> Self {
+        Self(core::sync::atomic::AtomicU64::new(0))
+    }
+    pub(crate) fn invalidate(&mut self) {
+        *self.0.get_mut() = 0;
+    }
+    fn get(&self) -> Option<u64> {
+        match self.0.load(core::sync::atomic::Ordering::Relaxed) {
+            0 => None,
+            hash => Some(hash),
+        }
+    }
+    fn set(&self, hash : u64) {
+        self.0.store(hash, core::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "merkle")]
+impl Clone for HashCache {
+    fn clone(&self) -> Self {
+        Self(core::sync::atomic::AtomicU64::new(self.0.load(core::sync::atomic::Ordering::Relaxed)))
+    }
+}
+
+#[cfg(not(feature = "merkle"))]
+#[derive(Clone)]
+pub(crate) struct HashCache;
+
+#[cfg(not(feature = "merkle"))]
+impl HashCache {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+    pub(crate) fn invalidate(&mut self) {}
+}
+
+impl PartialEq for HashCache {
+    /// the cache is not part of the tree contents
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "merkle")]
+impl<Item> BinTree<Item> {
+    /// merkle hash of the tree: each node hashes its value and the hashes of its children
+    /// hashes are cached in the nodes and only recomputed below a mutation
+    /// trees with the same items in a different shape have different hashes
+    /// uses the std DefaultHasher, so hashes should only be compared between builds of the same toolchain
+    pub fn root_hash(&self) -> u64 where Item : core::hash::Hash {
+        self.merkle_hash(&|item, state| item.hash(state))
+    }
+    /// compares the merkle hashes of two trees (O(1) when the hashes are cached)
+    /// true means same shape and same items (barring hash collisions)
+    pub fn merkle_eq(&self, other : &Self) -> bool where Item : core::hash::Hash {
+        self.root_hash() == other.root_hash()
+    }
+    /// merkle hash using a custom function to hash the items
+    /// a tree must always be hashed with the same function (the hashes are cached)
+    pub(crate) fn merkle_hash<F>(&self, hash_item : &F) -> u64 where F : Fn(&Item, &mut std::collections::hash_map::DefaultHasher) {
+        use core::hash::{Hash, Hasher};
+        if let Some(node) = self.root.as_deref() {
+            if let Some(hash) = node.hash.get() {
+                return hash;
+            }
+            let mut state = std::collections::hash_map::DefaultHasher::new();
+            hash_item(&node.value, &mut state);
+            node.left.merkle_hash(hash_item).hash(&mut state);
+            node.right.merkle_hash(hash_item).hash(&mut state);
+            // 0 is reserved for hashes that are not computed
+            let hash = state.finish().max(1);
+            node.hash.set(hash);
+            hash
+        } else {
+            0
+        }
+    }
+}
+
+/// some tests
+#[cfg(all(test, feature = "merkle"))]
+mod test {
+    use crate::{BinTree, tree, leaf};
+
+    #[test]
+    fn test_root_hash() {
+        let mut t1 = BinTree::from_sorted(0..100);
+        let mut t2 = BinTree::from_sorted(0..100);
+        assert!(t1.merkle_eq(&t2));
+        assert_eq!(BinTree::<i32>::new().root_hash(),0);
+        *t2.get_mut(&42).unwrap() = 1000;
+        assert!(!t1.merkle_eq(&t2));
+        *t2.get_mut(&1000).unwrap() = 42;
+        assert_eq!(t1.root_hash(),t2.root_hash());
+        t1.iter_mut().for_each(|x| *x += 1);
+        assert_eq!(t1.root_hash(),BinTree::from_sorted(1..101).root_hash());
+        // same items in a different shape
+        assert!(!tree(2,leaf(1),()).merkle_eq(&tree(1,(),leaf(2))));
+    }
+}
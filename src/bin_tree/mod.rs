@@ -1,55 +1,104 @@
+use alloc::boxed::Box;
+
 /// various tools for the binary tree
 pub mod utils;
-pub use self::utils::*;
 
 /// tools for the binary tree that use bits of unsafe code
 pub mod utils_unsafe;
-pub use self::utils_unsafe::*;
 
 /// tools for the binary tree (for insertions)
 pub mod utils_insertion;
-pub use self::utils_insertion::*;
 
 /// tools for the binary tree (for removals)
 pub mod utils_removal;
-pub use self::utils_removal::*;
 
 /// tools for the binary tree (for lookup/get)
 pub mod utils_lookup;
-pub use self::utils_lookup::*;
+
+/// tools for the binary tree (for retain/extract_if)
+pub(crate) mod utils_retain;
+pub use self::utils_retain::BinTreeExtractIf;
+pub(crate) use self::utils_retain::ExtractState;
 
 /// iterators (owned,ref,mut) over a binary tree with 4 traversal methods
 pub mod iter;
 pub use self::iter::*;
 
+/// in-order traversals with O(height) memory (stack iterators, morris_for_each_mut)
+mod iter_stack;
+pub use self::iter_stack::*;
+
+/// shape-preserving transforms (map, zip_with, fold, prune)
+mod transform;
+
+/// shape and content comparisons, equality ignores the stored heights
+mod compare;
+
+/// lowest common ancestor, depth and distance queries
+mod ancestors;
+
+/// visitor traits for custom walks with pruning
+mod visitor;
+pub use self::visitor::*;
+
+/// paths in a tree (sequences of left/right steps from the root)
+mod path;
+pub use self::path::*;
+
+/// parallel iterators and bulk operations (rayon feature)
+#[cfg(feature = "rayon")]
+mod par_iter;
+
+/// merkle hashing of subtrees (root_hash is only available with the merkle feature)
+mod merkle;
+use self::merkle::HashCache;
+
 /// a formatted tree container shows how to implement custom display behavior
 pub mod formatted;
 pub use self::formatted::*;
 
 /// a general purpose binary tree
-#[derive(Debug,Clone,PartialEq)]
+#[derive(Debug,Clone)]
 pub struct BinTree<Item> {
     root: Option<Box<BinTreeNode<Item>>>,
-    height: isize, // this field is only updated when representing balanced trees
+    height: isize, // kept up to date by every structural mutation (see recalculate_heights)
 }
 
 /// a general purpose binary tree node
-#[derive(Debug,Clone,PartialEq)]
+#[derive(Clone,PartialEq)]
 pub struct BinTreeNode<Item> {
     value : Item,
     left : BinTree<Item>,
     right : BinTree<Item>,
+    hash : HashCache, // invalidated by every mutable access to the node
+}
+
+impl<Item : core::fmt::Debug> core::fmt::Debug for BinTreeNode<Item> {
+    /// the hash cache is not part of the debug output
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BinTreeNode")
+            .field("value", &self.value)
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .finish()
+    }
 }
 
 impl<Item> BinTree<Item> {
     /// creates a branch
     pub fn new_node(value : Item, left: BinTree<Item>, right: BinTree<Item>) -> Self {
-        let height = std::cmp::max(left.height(),right.height()) + 1;
+        let height = core::cmp::max(left.height(),right.height()) + 1;
         Self { 
-            root : Some(Box::new(BinTreeNode{value, left, right})), 
+            root : Some(Box::new(BinTreeNode{value, left, right, hash: HashCache::new()})), 
             height,
         }
     }
+    /// mutable access to the root node (invalidates its hash)
+    fn root_mut(&mut self) -> Option<&mut BinTreeNode<Item>> {
+        let node = self.root.as_deref_mut()?;
+        node.hash.invalidate();
+        Some(node)
+    }
     /// creates a leaf
     pub fn new_leaf(item : Item) -> Self {
         Self::new_node(item, Self::new(), Self::new())
@@ -65,7 +114,7 @@ impl<Item> BinTree<Item> {
     /// height of tree
     pub fn update_height(&mut self) -> isize {
         if !self.is_empty() {
-            self.height = std::cmp::max(self.left().unwrap().height(),self.right().unwrap().height()) + 1;
+            self.height = core::cmp::max(self.left().unwrap().height(),self.right().unwrap().height()) + 1;
             self.height
         } else {
             0
@@ -86,8 +135,7 @@ impl<Item> BinTree<Item> {
             true
         } else {
             let b = self.balance();
-            b >= -1 && 
-            b <= 1 && 
+            (-1..=1).contains(&b) && 
             self.left().unwrap().is_balanced() &&
             self.right().unwrap().is_balanced()
         }
@@ -112,9 +160,8 @@ impl<Item> BinTree<Item> {
         if self.is_empty() {
             None
         } else {
-            match self.root.as_deref().expect("not empty") {
-                BinTreeNode { value, left, right } => Some((value,left,right)),
-            }
+            let BinTreeNode { value, left, right, .. } = self.root.as_deref().expect("not empty");
+            Some((value,left,right))
         }
     }
     /// splits a node into value, left and right (by ref mut)
@@ -122,9 +169,8 @@ impl<Item> BinTree<Item> {
         if self.is_empty() {
             None
         } else {
-            match self.root.as_deref_mut().expect("not empty") {
-                BinTreeNode { value, left, right } => Some((value,left,right)),
-            }
+            let BinTreeNode { value, left, right, .. } = self.root_mut().expect("not empty");
+            Some((value,left,right))
         }
     }
     /// splits a node into value, left and right (by move)
@@ -132,9 +178,8 @@ impl<Item> BinTree<Item> {
         if self.is_empty() {
             None
         } else {
-            match *self.root.expect("not empty") {
-                BinTreeNode { value, left, right } => Some((value,left,right)),
-            }
+            let BinTreeNode { value, left, right, .. } = *self.root.expect("not empty");
+            Some((value,left,right))
         }
     }
     /// returns a ref to the value at the top of the tree
@@ -166,7 +211,7 @@ impl<Item> BinTree<Item> {
         if self.is_empty() {
             None
         } else {
-            Some(&mut self.root.as_deref_mut().unwrap().value)
+            Some(&mut self.root_mut().unwrap().value)
         }
     }
     /// returns a mutable ref to the left branch of the tree
@@ -174,7 +219,7 @@ impl<Item> BinTree<Item> {
         if self.is_empty() {
             None
         } else {
-            Some(&mut self.root.as_deref_mut().unwrap().left)
+            Some(&mut self.root_mut().unwrap().left)
         }
     }
     /// returns a mutable ref to the right branch of the tree
@@ -182,7 +227,7 @@ impl<Item> BinTree<Item> {
         if self.is_empty() {
             None
         } else {
-            Some(&mut self.root.as_deref_mut().unwrap().right)
+            Some(&mut self.root_mut().unwrap().right)
         }
     }
     /// consumes self and returns the value at the top of the tree
@@ -214,10 +259,11 @@ impl<Item> BinTree<Item> {
         if self.is_empty() {
             *self = Self::new_node(value,left,right);
         } else {
-            let tree = self.root.as_deref_mut().unwrap();
+            let tree = self.root_mut().unwrap();
             tree.value = value;
             tree.left = left;
             tree.right = right;
+            self.update_height();
         }
     }
     /// sets value at root of tree
@@ -225,27 +271,31 @@ impl<Item> BinTree<Item> {
         if self.is_empty() {
             *self = Self::new_leaf(value);
         } else {
-            let tree = self.root.as_deref_mut().unwrap();
+            let tree = self.root_mut().unwrap();
             tree.value = value;
         }
     }
     /// sets left child at root of tree
+    /// the height of the root is adjusted
     pub fn set_left(&mut self, left : BinTree<Item>) -> bool {
         if self.is_empty() {
             false
         } else {
-            let tree = self.root.as_deref_mut().unwrap();
+            let tree = self.root_mut().unwrap();
             tree.left = left;
+            self.update_height();
             true
         }
     }
     /// sets right child at root of tree
+    /// the height of the root is adjusted
     pub fn set_right(&mut self, right : BinTree<Item>) -> bool {
         if self.is_empty() {
             false
         } else {
-            let tree = self.root.as_deref_mut().unwrap();
+            let tree = self.root_mut().unwrap();
             tree.right = right;
+            self.update_height();
             true
         }
     }
@@ -254,6 +304,8 @@ impl<Item> BinTree<Item> {
         *self = tree;
     }
     /// recalculate the height fields in the tree
+    /// only needed as a repair tool after mutating branches directly (left_mut, right_mut, node_mut)
+    /// because the tree methods keep heights up to date
     /// return true if any height changed ?
     pub fn recalculate_heights(&mut self) -> bool {
         self.recalculate_heights_rec(true, true, false).1
@@ -278,7 +330,7 @@ impl<Item> BinTree<Item> {
             if rec_right {
                 (height_right, changed_right) = right.recalculate_heights_rec(rec_left,rec_right,rebalance);
             }
-            let height_rec = std::cmp::max(height_left,height_right) + 1;
+            let height_rec = core::cmp::max(height_left,height_right) + 1;
             if changed_left || changed_right {
                 changed = true;
             }
@@ -0,0 +1,121 @@
+use alloc::vec::Vec;
+use rayon::iter::{ParallelIterator, split};
+
+use crate::BinTree;
+
+/// threshold under which par_from_sorted builds sequentially
+const SEQUENTIAL_BUILD : usize = 1024;
+
+/// part of a tree for the parallel iterators: the items of a branch (in order) followed by an optional value
+/// a piece splits at the root of its branch into (left branch, root value) and (right branch, trailing value)
+struct Piece<T, V> {
+    tree: Option<T>,
+    value: Option<V>,
+}
+
+impl<'a, Item> Piece<&'a BinTree<Item>, &'a Item> {
+    fn split(self) -> (Self, Option<Self>) {
+        match self.tree.and_then(BinTree::node) {
+            Some((value, left, right)) => (
+                Piece { tree: Some(left), value: Some(value) },
+                Some(Piece { tree: Some(right), value: self.value }),
+            ),
+            None => (Piece { tree: None, value: self.value }, None),
+        }
+    }
+    fn into_iter(self) -> impl Iterator<Item = &'a Item> {
+        self.tree.into_iter().flat_map(BinTree::iter).chain(self.value)
+    }
+}
+
+impl<'a, Item> Piece<&'a mut BinTree<Item>, &'a mut Item> {
+    fn split(self) -> (Self, Option<Self>) {
+        match self.tree.and_then(BinTree::node_mut) {
+            Some((value, left, right)) => (
+                Piece { tree: Some(left), value: Some(value) },
+                Some(Piece { tree: Some(right), value: self.value }),
+            ),
+            None => (Piece { tree: None, value: self.value }, None),
+        }
+    }
+    fn into_iter(self) -> impl Iterator<Item = &'a mut Item> {
+        self.tree.into_iter().flat_map(BinTree::iter_mut).chain(self.value)
+    }
+}
+
+impl<Item> Piece<BinTree<Item>, Item> {
+    fn split(self) -> (Self, Option<Self>) {
+        match self.tree.and_then(BinTree::into_node) {
+            Some((value, left, right)) => (
+                Piece { tree: Some(left), value: Some(value) },
+                Some(Piece { tree: Some(right), value: self.value }),
+            ),
+            None => (Piece { tree: None, value: self.value }, None),
+        }
+    }
+    fn into_iter(self) -> impl Iterator<Item = Item> {
+        self.tree.into_iter().flat_map(BinTree::into_iter).chain(self.value)
+    }
+}
+
+impl<Item> BinTree<Item> {
+    /// parallel iterator (rayon), splits at the roots of the branches
+    /// the order of the items is depth-first in-order (for collect)
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = &Item> where Item : Sync {
+        split(Piece { tree: Some(self), value: None }, |piece| piece.split()).flat_map_iter(|piece| piece.into_iter())
+    }
+    /// parallel mutable iterator (rayon), splits at the roots of the branches
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = &mut Item> where Item : Send {
+        split(Piece { tree: Some(self), value: None }, |piece| piece.split()).flat_map_iter(|piece| piece.into_iter())
+    }
+    /// parallel consuming iterator (rayon), splits at the roots of the branches
+    pub fn into_par_iter(self) -> impl ParallelIterator<Item = Item> where Item : Send {
+        split(Piece { tree: Some(self), value: None }, |piece| piece.split()).flat_map_iter(|piece| piece.into_iter())
+    }
+    /// parallel version of from_sorted: the branches are built with rayon::join
+    pub fn par_from_sorted(items : Vec<Item>) -> Self where Item : Send {
+        let mut items : Vec<_> = items.into_iter().map(Some).collect();
+        Self::par_from_sorted_rec(&mut items)
+    }
+    fn par_from_sorted_rec(items : &mut [Option<Item>]) -> Self where Item : Send {
+        if items.len() <= SEQUENTIAL_BUILD {
+            Self::from_sorted(items.iter_mut().map(|item| item.take().expect("each item is taken once")))
+        } else {
+            let (left, rest) = items.split_at_mut(items.len() / 2);
+            let (value, right) = rest.split_first_mut().expect("rest is not empty");
+            let (left, right) = rayon::join(|| Self::par_from_sorted_rec(left), || Self::par_from_sorted_rec(right));
+            Self::new_node(value.take().expect("each item is taken once"), left, right)
+        }
+    }
+    /// parallel map of the items keeping the shape of the tree (branches are mapped with rayon::join)
+    pub fn par_map<F, Mapped>(&self, f : &F) -> BinTree<Mapped> where Item : Sync, Mapped : Send, F : Fn(&Item) -> Mapped + Sync {
+        match self.node() {
+            Some((value, left, right)) => {
+                let (left, right) = rayon::join(|| left.par_map(f), || right.par_map(f));
+                BinTree::new_node(f(value), left, right)
+            },
+            None => BinTree::new(),
+        }
+    }
+}
+
+/// some tests
+#[cfg(test)]
+mod test {
+    use rayon::iter::ParallelIterator;
+    use crate::BinTree;
+
+    #[test]
+    fn test_par_iter() {
+        let mut t = BinTree::par_from_sorted((0..10_000).collect());
+        assert!(t.is_balanced());
+        assert!(!t.clone().recalculate_heights());
+        assert_eq!(t.par_iter().collect::<Vec<_>>(),t.iter().collect::<Vec<_>>());
+        t.par_iter_mut().for_each(|x| *x *= 2);
+        assert_eq!(t.par_iter().copied().max(),Some(19_998));
+        let doubled = t.par_map(&|x| *x as u64 + 1);
+        assert_eq!(doubled.height(),t.height());
+        assert_eq!(doubled.iter().take(3).collect::<Vec<_>>(),vec![&1,&3,&5]);
+        assert_eq!(t.into_par_iter().collect::<Vec<_>>(),(0..10_000).map(|x| x * 2).collect::<Vec<_>>());
+    }
+}
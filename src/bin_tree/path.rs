@@ -0,0 +1,157 @@
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+use crate::BinTree;
+
+/// one step from a node to one of its branches
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,PartialOrd,Ord)]
+pub enum TreeStep {
+    Left,
+    Right,
+}
+
+/// path from the root of a tree to a node (empty for the root)
+#[derive(Debug,Clone,Default,PartialEq,Eq,Hash,PartialOrd,Ord)]
+pub struct TreePath(Vec<TreeStep>);
+
+impl TreePath {
+    /// path to the root
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// number of steps (depth of the node)
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// is this the path to the root ?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// steps from the root
+    pub fn steps(&self) -> &[TreeStep] {
+        &self.0
+    }
+    /// add a step at the end of the path
+    pub fn push(&mut self, step : TreeStep) {
+        self.0.push(step);
+    }
+    /// remove the last step of the path
+    pub fn pop(&mut self) -> Option<TreeStep> {
+        self.0.pop()
+    }
+    /// path to a branch of the node
+    pub fn child(&self, step : TreeStep) -> Self {
+        let mut child = self.clone();
+        child.push(step);
+        child
+    }
+    /// longest path that starts both paths (path to the lowest common ancestor)
+    pub fn common_prefix(&self, other : &Self) -> Self {
+        self.0.iter().zip(&other.0).take_while(|(a, b)| a == b).map(|(a, _)| *a).collect()
+    }
+}
+
+impl From<Vec<TreeStep>> for TreePath {
+    fn from(steps : Vec<TreeStep>) -> Self {
+        Self(steps)
+    }
+}
+
+impl FromIterator<TreeStep> for TreePath {
+    fn from_iter<T: IntoIterator<Item = TreeStep>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<Item> BinTree<Item> {
+    /// branch of a node for a step (None for an empty tree)
+    fn branch(&self, step : TreeStep) -> Option<&Self> {
+        match step {
+            TreeStep::Left => self.left(),
+            TreeStep::Right => self.right(),
+        }
+    }
+    /// mutable branch of a node for a step (None for an empty tree)
+    fn branch_mut(&mut self, step : TreeStep) -> Option<&mut Self> {
+        match step {
+            TreeStep::Left => self.left_mut(),
+            TreeStep::Right => self.right_mut(),
+        }
+    }
+    /// subtree at the end of a path (possibly empty), None if the path goes through an empty tree
+    pub fn subtree(&self, path : &TreePath) -> Option<&Self> {
+        path.steps().iter().try_fold(self, |tree, step| tree.branch(*step))
+    }
+    /// mutable subtree at the end of a path (possibly empty), None if the path goes through an empty tree
+    /// the heights on the path are updated when the returned guard is dropped
+    pub fn subtree_mut(&mut self, path : &TreePath) -> Option<BinTreeSubtreeMut<'_, Item>> {
+        self.subtree(path)?;
+        Some(BinTreeSubtreeMut { tree : self, path : path.clone() })
+    }
+    /// mutable value of the node at the end of a path, None if the path doesn't lead to a node
+    /// (the shape and the heights are not affected)
+    pub fn value_at_mut(&mut self, path : &TreePath) -> Option<&mut Item> {
+        self.subtree_steps_mut(path.steps())?.value_mut()
+    }
+    /// mutable subtree at the end of the steps (the heights on the path are not updated)
+    fn subtree_steps_mut(&mut self, steps : &[TreeStep]) -> Option<&mut Self> {
+        steps.iter().try_fold(self, |tree, step| tree.branch_mut(*step))
+    }
+    /// updates the heights on the steps (bottom-up)
+    fn update_heights_on_steps(&mut self, steps : &[TreeStep]) {
+        if let Some((step, rest)) = steps.split_first() {
+            if let Some(branch) = self.branch_mut(*step) {
+                branch.update_heights_on_steps(rest);
+            }
+        }
+        self.update_height();
+    }
+    /// replaces the subtree at the end of a path (possibly empty) and returns the old one
+    /// heights on the path are adjusted (the tree is not rebalanced)
+    /// returns None (and drops tree) if the path goes through an empty tree
+    pub fn replace_subtree(&mut self, path : &TreePath, tree : Self) -> Option<Self> {
+        let old = core::mem::replace(self.subtree_steps_mut(path.steps())?, tree);
+        self.update_heights_on_steps(path.steps());
+        Some(old)
+    }
+    /// path to the shallowest node with the value (breadth-first search, for unsorted trees with duplicates)
+    pub fn path_to(&self, value : &Item) -> Option<TreePath> where Item : PartialEq {
+        self.iter_bfs_with_path().find(|(_, _, item)| *item == value).map(|(_, path, _)| path)
+    }
+    /// paths to all the nodes with their values (depth-first pre-order)
+    pub fn paths(&self) -> impl Iterator<Item = (TreePath, &Item)> {
+        self.iter_dfs_pre_with_path().map(|(_, path, value)| (path, value))
+    }
+}
+
+/// mutable access to a subtree, the heights on the path are updated when dropped
+pub struct BinTreeSubtreeMut<'a, Item> {
+    tree : &'a mut BinTree<Item>,
+    path : TreePath,
+}
+
+impl<Item> BinTreeSubtreeMut<'_, Item> {
+    /// path of the subtree
+    pub fn path(&self) -> &TreePath {
+        &self.path
+    }
+}
+
+impl<Item> Deref for BinTreeSubtreeMut<'_, Item> {
+    type Target = BinTree<Item>;
+    fn deref(&self) -> &Self::Target {
+        self.tree.subtree(&self.path).expect("path checked by subtree_mut")
+    }
+}
+
+impl<Item> DerefMut for BinTreeSubtreeMut<'_, Item> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.tree.subtree_steps_mut(self.path.steps()).expect("path checked by subtree_mut")
+    }
+}
+
+impl<Item> Drop for BinTreeSubtreeMut<'_, Item> {
+    /// updates the heights on the path
+    fn drop(&mut self) {
+        self.tree.update_heights_on_steps(self.path.steps());
+    }
+}
@@ -37,19 +37,19 @@ fn eq_test() {
 #[test]
 fn basic_access_test() {
     let mut tree : BinTree<i32> = Default::default();
-    assert_eq!(tree.is_empty(),true);
+    assert!(tree.is_empty());
     tree = BinTree::new_leaf(10);
-    assert_eq!(tree.is_leaf(),true);
+    assert!(tree.is_leaf());
     assert_eq!(tree.value().unwrap(),&10);
     *tree.left_mut().unwrap() = BinTree::new_leaf(20);
-    assert_eq!(tree.is_leaf(),false);
-    assert_eq!(tree.is_branch(),true);
+    assert!(!tree.is_leaf());
+    assert!(tree.is_branch());
     assert_eq!(tree.to_string(),"((20) <= 10)");
     *tree.left_mut().unwrap() = BinTree::new();
     *tree.right_mut().unwrap() = BinTree::new_leaf(30);
-    assert_eq!(tree.is_empty(),false);
-    assert_eq!(tree.is_leaf(),false);
-    assert_eq!(tree.is_branch(),true);
+    assert!(!tree.is_empty());
+    assert!(!tree.is_leaf());
+    assert!(tree.is_branch());
     assert_eq!(tree.to_string(),"(10 => (30))");
     *tree.get_mut(&30).unwrap() = 40;
     assert_eq!(tree.to_string(),"(10 => (40))");
@@ -115,11 +115,11 @@ fn into_iter_order_test() {
 #[test]
 fn iter_order_test() {
     let t = test_tree();
-    assert_eq!(t.iter().map(|i|i.clone()).collect::<Vec<_>>(),vec![3, 2, 1, 4, 6, 5]);
-    assert_eq!(t.iter_dfs_in().map(|i|i.clone()).collect::<Vec<_>>(),vec![3, 2, 1, 4, 6, 5]);
-    assert_eq!(t.iter_dfs_pre().map(|i|i.clone()).collect::<Vec<_>>(),vec![1, 2, 3, 4, 5, 6]);
-    assert_eq!(t.iter_dfs_post().map(|i|i.clone()).collect::<Vec<_>>(),vec![3, 2, 6, 5, 4, 1]);
-    assert_eq!(t.iter_bfs().map(|i|*i).collect::<Vec<_>>(),vec![1, 2, 4, 3, 5, 6]);
+    assert_eq!(t.iter().copied().collect::<Vec<_>>(),vec![3, 2, 1, 4, 6, 5]);
+    assert_eq!(t.iter_dfs_in().copied().collect::<Vec<_>>(),vec![3, 2, 1, 4, 6, 5]);
+    assert_eq!(t.iter_dfs_pre().copied().collect::<Vec<_>>(),vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(t.iter_dfs_post().copied().collect::<Vec<_>>(),vec![3, 2, 6, 5, 4, 1]);
+    assert_eq!(t.iter_bfs().copied().collect::<Vec<_>>(),vec![1, 2, 4, 3, 5, 6]);
 }
 
 #[test]
@@ -142,17 +142,17 @@ fn iter_mut_order_test() {
 fn take_replace_swap_test() {
     let mut t = test_tree();
 
-    let value1 = std::mem::take(t.get_mut(&5).unwrap());
+    let value1 = core::mem::take(t.get_mut(&5).unwrap());
     assert_eq!(value1,5);
     assert_eq!(t.to_string(),"(((3) <= 2) <= 1 => (4 => ((6) <= 0)))");
 
-    let value2 = std::mem::replace(t.get_mut(&4).unwrap(),value1);
+    let value2 = core::mem::replace(t.get_mut(&4).unwrap(),value1);
     assert_eq!(value2,4);
     assert_eq!(t.to_string(),"(((3) <= 2) <= 1 => (5 => ((6) <= 0)))");
 
-    assert_eq!(t.swap(&3, &1).is_ok(),true);
-    assert_eq!(t.swap(&5, &6).is_ok(),true);
-    assert_eq!(t.swap(&0, &6).is_ok(),true);
+    assert!(t.swap(&3, &1).is_ok());
+    assert!(t.swap(&5, &6).is_ok());
+    assert!(t.swap(&0, &6).is_ok());
     assert_eq!(t.swap(&1, &1),Err(BinTreeError::SwapSame));
     assert_eq!(t.swap(&1, &10),Err(BinTreeError::SwapNotFound2));
     assert_eq!(t.swap(&10, &1),Err(BinTreeError::SwapNotFound1));
@@ -179,11 +179,11 @@ fn push_sorted_test() {
 #[test]
 fn push_sorted_unique_test() {
     let mut t = BinTree::new();
-    assert_eq!(t.insert_unique(4),true);
-    assert_eq!(t.insert_unique(2),true);
-    assert_eq!(t.insert_unique(8),true);
-    assert_eq!(t.insert_unique(1),true);
-    assert_eq!(t.insert_unique(2),false);
+    assert!(t.insert_unique(4));
+    assert!(t.insert_unique(2));
+    assert!(t.insert_unique(8));
+    assert!(t.insert_unique(1));
+    assert!(!t.insert_unique(2));
     assert_eq!(t.to_string(),"(((1) <= 2) <= 4 => (8))");
     assert_eq!(t.to_vec(),vec![1,2,4,8]);
     assert_eq!(t.extend_sorted_unique(vec![18,6,3,8,5,11]),5);
@@ -450,22 +450,35 @@ fn pop_sorted_height_test() {
     assert_eq!(String::from_iter(t.to_vec().iter())," !ESTaefghilmnorstvy");
     assert_eq!(t.height(),5);
 
-    assert_eq!(t.recalculate_heights(),false);
+    assert!(!t.recalculate_heights());
     assert_eq!(t.pop_sorted(true),Some('h'));
-    assert_eq!(t.recalculate_heights(),false);
+    assert!(!t.recalculate_heights());
     assert_eq!(t.pop_sorted(true),Some('i'));
-    assert_eq!(t.recalculate_heights(),false);
+    assert!(!t.recalculate_heights());
     assert_eq!(t.pop_sorted(true),Some('l'));
-    assert_eq!(t.recalculate_heights(),false);
+    assert!(!t.recalculate_heights());
     assert_eq!(t.pop_sorted(true),Some('m'));
-    assert_eq!(t.recalculate_heights(),false);
+    assert!(!t.recalculate_heights());
 
     for _ in 0..t.len() {
-        assert_eq!(t.pop_sorted(true).is_some(),true);
-        assert_eq!(t.recalculate_heights(),false);
+        assert!(t.pop_sorted(true).is_some());
+        assert!(!t.recalculate_heights());
     }
 
-    assert_eq!(t.is_empty(),true);        
+    assert!(t.is_empty());        
+}
+#[test]
+fn pop_sorted_rebalance_test() {
+    let mut t = BinTree::new();
+    for v in [4, 2, 5, 1, 3] {
+        t.insert(v);
+    }
+    assert!(t.is_balanced());
+    // 5 replaces the root and the right branch becomes empty
+    assert_eq!(t.pop_sorted(true), Some(4));
+    assert!(t.is_balanced());
+    assert!(!t.recalculate_heights());
+    assert_eq!(t.to_vec(), vec![1, 2, 3, 5]);
 }
 #[test]
 fn remove_sorted_height_test() {
@@ -478,16 +491,16 @@ fn remove_sorted_height_test() {
     assert_eq!(String::from_iter(t.to_vec().iter())," !ESTaefghilmnorstvy");
     assert_eq!(t.height(),5);
 
-    assert_eq!(t.recalculate_heights(),false);
+    assert!(!t.recalculate_heights());
     assert_eq!(t.remove_sorted(&'r'),Some('r'));
-    assert_eq!(t.recalculate_heights(),false);
+    assert!(!t.recalculate_heights());
 
     for c in t.to_vec() {
         assert_eq!(t.remove_sorted(&c),Some(c));
-        assert_eq!(t.recalculate_heights(),false);
+        assert!(!t.recalculate_heights());
     }
 
-    assert_eq!(t.is_empty(),true);        
+    assert!(t.is_empty());        
 }
 
 // to enable random balancing test, test with 
@@ -504,19 +517,412 @@ fn random_balance_test() {
 
     t.insert_unique(n);
     t.remove_sorted(&n);
-    assert_eq!(t.is_empty(),true);
+    assert!(t.is_empty());
 
     for _ in 0..n {
         t.insert_unique(rng.gen());
-        assert_eq!(t.recalculate_heights(),false);
-        assert_eq!(t.is_balanced(),true);
+        assert!(!t.recalculate_heights());
+        assert!(t.is_balanced());
     }
     // height should not exceed 1.44*log2(n)
     let max_height = (1.44 * (n as f64).log2()).ceil() as isize;
     assert!(t.height() <= max_height);
     for _ in 0..rn {
         t.remove_sorted(&rng.gen());
-        assert_eq!(t.recalculate_heights(),false);
-        assert_eq!(t.is_balanced(),true);
+        assert!(!t.recalculate_heights());
+        assert!(t.is_balanced());
+    }
+}
+
+#[test]
+fn pop_min_max_sorted_test() {
+    let mut t = BinTree::new();
+    t.extend_sorted_unique(vec![18,6,3,8,5,11,1,7,2,10,9]);
+    let mut popped = vec![];
+    while !t.is_empty() {
+        popped.push(t.pop_min_sorted(true).unwrap());
+        assert!(!t.recalculate_heights());
+        assert!(t.is_balanced());
+        if let Some(max) = t.pop_max_sorted(true) {
+            popped.push(max);
+        }
+        assert!(!t.recalculate_heights());
+        assert!(t.is_balanced());
+    }
+    assert_eq!(popped,vec![1,18,2,11,3,10,5,9,6,8,7]);
+    assert_eq!(t.pop_min_sorted(true),None);
+    assert_eq!(t.pop_max_sorted(true),None);
+}
+
+#[test]
+fn get_or_insert_test() {
+    // the item is found again after the rotations of every kind
+    for step in [1,63,7,31] {
+        let mut t = BinTree::new();
+        for i in 0..64 {
+            let key = i * step % 64;
+            let item = t.get_or_insert_to_key_cmp(key, |key| (key, 0), |(key, _)| key, i32::partial_cmp, true);
+            assert_eq!(*item,(key, 0));
+            item.1 = i;
+            assert!(t.is_balanced());
+            assert!(!t.clone().recalculate_heights());
+        }
+        let item = t.get_or_insert_to_key_cmp(step, |_| unreachable!(), |(key, _)| key, i32::partial_cmp, true);
+        assert_eq!(item.0,step);
+        assert_eq!(t.iter().map(|(key, _)| *key).collect::<Vec<_>>(),(0..64).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn incomparable_checked_test() {
+    let mut t = BinTree::new();
+    t.extend_sorted(vec![2.0,1.0,3.0]);
+    let cmp = f64::partial_cmp;
+    assert_eq!(t.insert_to_key_cmp_checked(f64::NAN,|x|x,cmp,true,true),Err(BinTreeError::Incomparable));
+    assert_eq!(t.get_sorted_to_key_cmp_checked(&f64::NAN,|x|x,cmp),Err(BinTreeError::Incomparable));
+    assert_eq!(t.get_mut_sorted_to_key_cmp_checked(&f64::NAN,|x|x,cmp),Err(BinTreeError::Incomparable));
+    assert_eq!(t.remove_sorted_to_key_cmp_checked(&f64::NAN,|x|x,cmp,true),Err(BinTreeError::Incomparable));
+    assert_eq!(t.to_vec(),vec![1.0,2.0,3.0]);
+    assert_eq!(t.get_sorted_to_key_cmp_checked(&3.0,|x|x,cmp),Ok(Some(&3.0)));
+    assert_eq!(t.remove_sorted_to_key_cmp_checked(&3.0,|x|x,cmp,true),Ok(Some(3.0)));
+    assert_eq!(t.remove_sorted_to_key_cmp_checked(&3.0,|x|x,cmp,true),Ok(None));
+    assert_eq!(t.insert_to_key_cmp_checked(3.0,|x|x,cmp,true,true),Ok(None));
+    assert_eq!(t.insert_to_key_cmp_checked(3.0,|x|x,cmp,true,true),Ok(Some(3.0)));
+    // NaN can't be compared with itself, so it is rejected even from an empty tree
+    let mut t = BinTree::new();
+    assert_eq!(t.insert_to_key_cmp_checked(f64::NAN,|x|x,cmp,true,true),Err(BinTreeError::Incomparable));
+    assert!(t.is_empty());
+}
+
+#[test]
+fn try_ops_test() {
+    let mut t = BinTree::new();
+    assert_eq!(t.try_insert(2),Ok(()));
+    assert_eq!(t.try_insert(1),Ok(()));
+    assert_eq!(t.try_insert(2),Err(BinTreeError::DuplicateKey { key: String::from("2") }));
+    assert_eq!(t.try_swap(&1,&5),Err(BinTreeError::KeyNotFound { key: String::from("5") }));
+    assert_eq!(t.try_swap(&1,&1),Err(BinTreeError::SwapSame));
+    assert_eq!(t.try_remove_sorted(&5),Err(BinTreeError::key_not_found(&5)));
+    assert_eq!(t.try_remove_sorted(&1),Ok(1));
+    assert_eq!(t.try_remove(&2),Ok(2));
+    assert_eq!(t.try_remove(&2).unwrap_err().to_string(),"key not found: 2");
+    assert_eq!(BinTree::try_new_leaf(7),Ok(leaf(7)));
+    assert_eq!(BinTreeError::Incomparable.to_string(),"keys cannot be compared");
+}
+
+#[test]
+fn unsorted_heights_test() {
+    fn heights_ok(t : &BinTree<i32>) -> bool {
+        !t.clone().recalculate_heights()
+    }
+    let mut t = test_tree();
+    t.push_left(10);
+    t.push_right(11);
+    assert!(heights_ok(&t));
+    assert_eq!(t.remove(&6),Some(6));
+    assert!(heights_ok(&t));
+    assert_eq!(t.pop_left(),Some(10));
+    assert_eq!(t.pop_right(),Some(11));
+    assert!(heights_ok(&t));
+    assert_eq!(t.pop_tree().map(|p| p.height()),Some(1));
+    assert_eq!(t.pop(),Some(2));
+    assert!(heights_ok(&t));
+    t.set_left(tree(20,leaf(21),leaf(22)));
+    assert_eq!(t.height(),3);
+    t.set_left(BinTree::new());
+    t.set_right(BinTree::new());
+    assert_eq!(t.height(),1);
+    t.set_node(30,leaf(31),BinTree::new());
+    assert_eq!(t.height(),2);
+    assert!(heights_ok(&t));
+}
+
+#[test]
+fn retain_extract_if_test() {
+    let mut t = BinTree::from_sorted(0..10);
+    t.retain(|x| x % 3 != 0);
+    assert_eq!(t.to_vec(),vec![1,2,4,5,7,8]);
+    let even : Vec<_> = t.extract_if(|x| *x % 2 == 0).collect();
+    assert_eq!(even,vec![2,4,8]);
+    assert_eq!(t.to_vec(),vec![1,5,7]);
+    assert!(!t.clone().recalculate_heights());
+    // items not visited before the drop are kept
+    assert_eq!(t.extract_if(|_| true).next(),Some(1));
+    assert_eq!(t.to_vec(),vec![5,7]);
+    assert!(!t.clone().recalculate_heights());
+    // a balanced tree stays balanced, even when a whole side is removed
+    let mut t = BinTree::from_sorted(0..1000);
+    t.retain(|x| *x >= 700 || x % 50 == 0);
+    assert_eq!(t.len(),314);
+    assert!(t.is_balanced());
+    assert!(!t.clone().recalculate_heights());
+    assert_eq!(t.extract_if(|x| *x % 3 != 0).take(100).count(),100);
+    assert!(t.is_balanced());
+    assert!(!t.clone().recalculate_heights());
+    assert!(t.iter().zip(t.iter().skip(1)).all(|(x,y)| x < y));
+    // the shape is kept: a node with one branch is replaced by it
+    let mut t = test_tree();
+    t.retain(|x| *x != 4);
+    assert_eq!(t,tree(1,tree(2,leaf(3),()),tree(5,leaf(6),())));
+    assert!(!t.clone().recalculate_heights());
+    // a node with 2 branches is replaced by the first item of its right branch
+    let mut t = test_tree();
+    assert_eq!(t.extract_if(|x| *x == 1).collect::<Vec<_>>(),vec![1]);
+    assert_eq!(t,tree(4,tree(2,leaf(3),()),tree(5,leaf(6),())));
+    assert!(!t.clone().recalculate_heights());
+}
+
+#[test]
+fn path_iter_test() {
+    use crate::TreeStep::*;
+
+    let t = test_tree();
+    let bfs : Vec<_> = t.iter_bfs_with_path().map(|(d,p,v)| (d,p.steps().to_vec(),*v)).collect();
+    assert_eq!(bfs,vec![(0,vec![],1),(1,vec![Left],2),(1,vec![Right],4),
+        (2,vec![Left,Left],3),(2,vec![Right,Right],5),(3,vec![Right,Right,Left],6)]);
+    assert_eq!(t.iter_dfs_in_with_path().map(|(_,_,v)| *v).collect::<Vec<_>>(),t.iter_dfs_in().copied().collect::<Vec<_>>());
+    assert_eq!(t.iter_dfs_pre_with_path().map(|(_,_,v)| *v).collect::<Vec<_>>(),t.iter_dfs_pre().copied().collect::<Vec<_>>());
+    assert_eq!(t.iter_dfs_post_with_path().map(|(d,_,_)| d).collect::<Vec<_>>(),vec![2,1,3,2,1,0]);
+    assert_eq!(t.levels().collect::<Vec<_>>(),vec![vec![&1],vec![&2,&4],vec![&3,&5],vec![&6]]);
+    assert_eq!(BinTree::<i32>::new().levels().count(),0);
+}
+
+#[test]
+fn stack_iter_test() {
+    let mut t = test_tree();
+    let in_order : Vec<_> = t.iter_dfs_in().copied().collect();
+    assert_eq!(t.iter_stack().copied().collect::<Vec<_>>(),in_order);
+    t.iter_mut_stack().for_each(|x| *x *= 10);
+    assert_eq!(t.iter_stack().copied().collect::<Vec<_>>(),in_order.iter().map(|x| x * 10).collect::<Vec<_>>());
+    let mut visited = Vec::new();
+    t.morris_for_each_mut(|x| { visited.push(*x); *x /= 10; });
+    assert_eq!(visited,in_order.iter().map(|x| x * 10).collect::<Vec<_>>());
+    assert_eq!(t,test_tree());
+    assert_eq!(BinTree::<i32>::new().iter_stack().count(),0);
+    BinTree::<i32>::new().morris_for_each_mut(|_| unreachable!());
+    // paths longer than 64 levels, to the right and to the left
+    let mut t = BinTree::new();
+    t.extend_right(0..200);
+    t.left_mut().unwrap().extend_left(200..300);
+    t.update_height();
+    let in_order : Vec<_> = t.iter_dfs_in().map(|x| x + 1).collect();
+    t.morris_for_each_mut(|x| *x += 1);
+    assert_eq!(t.to_vec(),in_order);
+    assert!(!t.clone().recalculate_heights());
+
+    // the links are removed when the closure panics
+    let mut t = BinTree::from_sorted(0..100);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        t.morris_for_each_mut(|x| if *x == 42 { panic!("stop") } else { *x += 1 });
+    }));
+    assert!(result.is_err());
+    assert_eq!(t.to_vec(),(1..43).chain(42..100).collect::<Vec<_>>());
+    assert!(!t.clone().recalculate_heights());
+}
+
+#[test]
+fn visitor_test() {
+    use crate::{BinTreeVisitor, BinTreeVisitorMut, VisitControl};
+
+    /// records the callbacks, skips below max_depth and stops at a value
+    struct Trace { events : Vec<String>, max_depth : usize, stop_at : i32 }
+    impl BinTreeVisitor<i32> for Trace {
+        fn enter_node(&mut self, value : &i32, depth : usize) -> VisitControl {
+            self.events.push(format!("<{}", value));
+            if depth >= self.max_depth { VisitControl::SkipChildren } else { VisitControl::Continue }
+        }
+        fn visit_value(&mut self, value : &i32, _depth : usize) -> VisitControl {
+            self.events.push(format!("{}", value));
+            if *value == self.stop_at { VisitControl::Stop } else { VisitControl::Continue }
+        }
+        fn leave_node(&mut self, value : &i32, _depth : usize) -> VisitControl {
+            self.events.push(format!("{}>", value));
+            VisitControl::Continue
+        }
+        fn visit_empty(&mut self, _depth : usize) -> VisitControl {
+            self.events.push("_".into());
+            VisitControl::Continue
+        }
+    }
+
+    let t = test_tree();
+    let mut trace = Trace { events : vec![], max_depth : 10, stop_at : 0 };
+    assert_eq!(t.walk(&mut trace),VisitControl::Continue);
+    assert_eq!(trace.events.join(" "),"<1 <2 <3 _ 3 _ 3> 2 _ 2> 1 <4 _ 4 <5 <6 _ 6 _ 6> 5 _ 5> 4> 1>");
+    let mut trace = Trace { events : vec![], max_depth : 1, stop_at : 0 };
+    assert_eq!(t.walk(&mut trace),VisitControl::Continue);
+    assert_eq!(trace.events.join(" "),"<1 <2 2 2> 1 <4 4 4> 1>");
+    let mut trace = Trace { events : vec![], max_depth : 10, stop_at : 4 };
+    assert_eq!(t.walk(&mut trace),VisitControl::Stop);
+    assert_eq!(trace.events.last().unwrap(),"4");
+    let mut trace = Trace { events : vec![], max_depth : 10, stop_at : 0 };
+    assert_eq!(BinTree::new().walk(&mut trace),VisitControl::Continue);
+    assert_eq!(trace.events,vec!["_"]);
+
+    /// adds the depth to the values down to max_depth
+    struct AddDepth { max_depth : usize }
+    impl BinTreeVisitorMut<i32> for AddDepth {
+        fn enter_node(&mut self, value : &mut i32, depth : usize) -> VisitControl {
+            *value += 10 * depth as i32;
+            if depth >= self.max_depth { VisitControl::SkipChildren } else { VisitControl::Continue }
+        }
+    }
+    let mut t = test_tree();
+    assert_eq!(t.walk_mut(&mut AddDepth { max_depth : 2 }),VisitControl::Continue);
+    assert_eq!(t.iter_dfs_pre().copied().collect::<Vec<_>>(),vec![1,12,23,14,25,6]);
+    assert!(!t.clone().recalculate_heights());
+}
+
+#[test]
+fn transform_test() {
+    /// an expression tree
+    #[derive(Debug,Clone,PartialEq)]
+    enum Expr { Num(i32), Add, Mul }
+    use Expr::*;
+
+    let e = tree(Add, tree(Mul, leaf(Num(2)), leaf(Num(3))), leaf(Num(4)));
+    let eval = |e : &BinTree<Expr>| e.fold(0, |_, x, l, r| match x { Num(n) => *n, Add => l + r, Mul => l * r });
+    assert_eq!(eval(&e),10);
+    // acc is passed to every node and given back by every empty branch (5 nodes, 6 empty branches)
+    assert_eq!(e.fold(1, |acc, _, l, r| acc + l + r),11);
+    assert_eq!(e.fold_bottom_up(0, |_, l : usize, r| l + r + 1),5);
+
+    let doubled = e.map_ref(|x| match x { Num(n) => Num(n * 2), op => op.clone() });
+    assert_eq!(eval(&doubled),32);
+    assert_eq!(doubled.height(),e.height());
+    let names = e.clone().map(|x| format!("{:?}", x));
+    assert_eq!(names.iter_dfs_pre().cloned().collect::<Vec<_>>(),vec!["Add","Mul","Num(2)","Num(3)","Num(4)"]);
+
+    let sums = e.zip_with(&doubled, |x, y| match (x, y) { (Num(a), Num(b)) => a + b, _ => 0 }).unwrap();
+    assert_eq!(sums.iter_dfs_in().copied().collect::<Vec<_>>(),vec![6,0,9,0,12]);
+    assert_eq!(e.zip_with(&leaf(1), |_, _| ()),None);
+
+    let nums = e.clone().filter_map(|x| if x == Mul { None } else { Some(x) });
+    assert_eq!(nums,tree(Add, (), leaf(Num(4))));
+
+    let mut t = test_tree();
+    t.prune(|x| *x != 5);
+    assert_eq!(t,tree(1, tree(2, leaf(3), ()), leaf(4)));
+    assert_eq!(t.height(),3);
+    assert!(!t.clone().recalculate_heights());
+    t.prune(|x| *x != 1);
+    assert!(t.is_empty());
+    assert_eq!(t.height(),0);
+}
+
+#[test]
+fn subtree_path_test() {
+    use crate::{TreePath, TreeStep::*};
+
+    // duplicate values: lookup by position
+    let mut t = tree(1, tree(2, leaf(3), ()), tree(2, (), leaf(3)));
+    let right : TreePath = vec![Right].into();
+    assert_eq!(t.subtree(&right).unwrap().to_vec(),vec![2,3]);
+    assert!(t.subtree(&vec![Left,Right].into()).unwrap().is_empty());
+    assert!(t.subtree(&vec![Left,Right,Left].into()).is_none());
+    assert_eq!(t.path_to(&3),Some(vec![Left,Left].into()));
+    assert_eq!(t.path_to(&4),None);
+    let paths : Vec<_> = t.paths().filter(|(_, v)| **v == 2).map(|(p, _)| p).collect();
+    assert_eq!(paths,vec![vec![Left].into(),right.clone()]);
+
+    // growing a subtree updates the heights on the path
+    {
+        let mut sub = t.subtree_mut(&vec![Right,Right].into()).unwrap();
+        sub.set_left(tree(4, leaf(5), ()));
+        assert_eq!(sub.height(),3);
+    }
+    assert_eq!(t.height(),5);
+    assert!(!t.clone().recalculate_heights());
+    *t.subtree_mut(&right).unwrap().value_mut().unwrap() = 20;
+    assert_eq!(t.subtree(&right).unwrap().value(),Some(&20));
+
+    let old = t.replace_subtree(&right, BinTree::new()).unwrap();
+    assert_eq!(old.to_vec(),vec![20,5,4,3]);
+    assert_eq!(t.height(),3);
+    let empty = t.replace_subtree(&vec![Left,Right].into(), leaf(7)).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(t.to_vec(),vec![3,2,7,1]);
+    assert_eq!(t.replace_subtree(&vec![Right,Left].into(), leaf(8)),None);
+    assert!(!t.clone().recalculate_heights());
+}
+
+#[test]
+fn ancestors_test() {
+    let t = test_tree();
+    assert_eq!(t.lowest_common_ancestor(&3,&6),Some(&1));
+    assert_eq!(t.lowest_common_ancestor(&6,&4),Some(&4));
+    assert_eq!(t.lowest_common_ancestor(&3,&3),Some(&3));
+    assert_eq!(t.lowest_common_ancestor(&3,&7),None);
+    assert_eq!(t.depth_of(&6),Some(3));
+    assert_eq!(t.depth_of(&7),None);
+    assert_eq!(t.distance(&3,&6),Some(5));
+    assert_eq!(t.distance(&5,&5),Some(0));
+
+    let s = BinTree::from_sorted(0..15);
+    assert_eq!(s.value(),Some(&7));
+    assert_eq!(s.lowest_common_ancestor_sorted(&0,&6),Some(&3));
+    assert_eq!(s.lowest_common_ancestor_sorted(&8,&14),Some(&11));
+    assert_eq!(s.lowest_common_ancestor_sorted(&3,&7),Some(&7));
+    assert_eq!(s.lowest_common_ancestor_sorted(&0,&20),None);
+    assert_eq!(s.lowest_common_ancestor_sorted(&0,&6),s.lowest_common_ancestor(&0,&6));
+    assert_eq!(s.depth_of_sorted(&0),Some(3));
+    assert_eq!(s.depth_of_sorted(&7),Some(0));
+    assert_eq!(s.distance_sorted(&0,&14),Some(6));
+    assert_eq!(s.distance_sorted(&0,&2),Some(2));
+    assert_eq!(s.distance_sorted(&0,&2),s.distance(&0,&2));
+    assert_eq!(BinTree::<i32>::new().depth_of_sorted(&0),None);
+}
+
+#[test]
+fn compare_test() {
+    let t = test_tree();
+    let mut stale = test_tree();
+    stale.height = 42;
+    assert_eq!(t,stale);
+    assert!(t.shape_eq(&stale));
+    let balanced = BinTree::from_sorted(t.iter_dfs_in().copied().collect::<Vec<_>>());
+    assert!(t.content_eq(&balanced));
+    assert!(!t.shape_eq(&balanced));
+    assert!(!t.same_shape(&balanced));
+    let strings = t.map_ref(|x| x.to_string());
+    assert!(t.same_shape(&strings));
+    let mut other = test_tree();
+    *other.value_mut().unwrap() = 10;
+    assert!(t.same_shape(&other));
+    assert!(!t.content_eq(&other));
+    assert_ne!(t,other);
+    assert!(BinTree::<i32>::new().content_eq(&BinTree::new()));
+}
+
+#[test]
+fn size_hint_test() {
+    // heights can be stale after left_mut, so plain tree iterators only have a lower bound
+    let mut t = test_tree();
+    *t.left_mut().unwrap() = BinTree::from_sorted(0..100);
+    assert_eq!(t.iter().size_hint(),(1,None));
+    assert_eq!(BinTree::<i32>::new().iter().size_hint(),(0,None));
+    for mut iter in [t.iter_dfs_in(),t.iter_dfs_pre(),t.iter_dfs_post(),t.iter_bfs()] {
+        for remaining in (0..=104).rev() {
+            let (lower,upper) = iter.size_hint();
+            assert!(lower <= remaining && (lower > 0) == (remaining > 0));
+            assert_eq!(upper,None);
+            iter.next();
+        }
     }
+    let mut iter = t.clone().into_iter_bfs();
+    iter.next();
+    assert_eq!(iter.size_hint(),(2,None));
+    assert_eq!(iter.count(),103);
+    let mut iter = t.iter_mut();
+    iter.next();
+    assert!(iter.size_hint().0 >= 1);
+    assert_eq!(t.iter().count(),104);
+    // exact when the length is known (iterators of maps and sets)
+    let mut iter = t.iter().with_len(104);
+    assert_eq!(iter.len(),104);
+    iter.nth(99);
+    assert_eq!(iter.len(),4);
+    assert_eq!(iter.by_ref().count(),4);
+    assert_eq!(iter.len(),0);
+    assert_eq!(iter.next(),None);
 }
@@ -0,0 +1,112 @@
+use crate::BinTree;
+
+impl<Item> BinTree<Item> {
+    /// new tree with the same shape and f applied to every value (called in pre-order)
+    pub fn map<U>(self, mut f : impl FnMut(Item) -> U) -> BinTree<U> {
+        self.map_rec(&mut f)
+    }
+    /// recursive map
+    fn map_rec<U, F : FnMut(Item) -> U>(self, f : &mut F) -> BinTree<U> {
+        match self.into_node() {
+            None => BinTree::new(),
+            Some((value, left, right)) => {
+                let value = f(value);
+                BinTree::new_node(value, left.map_rec(f), right.map_rec(f))
+            }
+        }
+    }
+    /// new tree with the same shape and f applied to a reference to every value (called in pre-order)
+    pub fn map_ref<U>(&self, mut f : impl FnMut(&Item) -> U) -> BinTree<U> {
+        self.map_ref_rec(&mut f)
+    }
+    /// recursive map_ref
+    fn map_ref_rec<U, F : FnMut(&Item) -> U>(&self, f : &mut F) -> BinTree<U> {
+        match self.node() {
+            None => BinTree::new(),
+            Some((value, left, right)) => {
+                let value = f(value);
+                BinTree::new_node(value, left.map_ref_rec(f), right.map_ref_rec(f))
+            }
+        }
+    }
+    /// same as map but a subtree is dropped when f returns None for its root (called in pre-order)
+    pub fn filter_map<U>(self, mut f : impl FnMut(Item) -> Option<U>) -> BinTree<U> {
+        self.filter_map_rec(&mut f)
+    }
+    /// recursive filter_map
+    fn filter_map_rec<U, F : FnMut(Item) -> Option<U>>(self, f : &mut F) -> BinTree<U> {
+        match self.into_node() {
+            None => BinTree::new(),
+            Some((value, left, right)) => match f(value) {
+                None => BinTree::new(),
+                Some(value) => BinTree::new_node(value, left.filter_map_rec(f), right.filter_map_rec(f)),
+            }
+        }
+    }
+    /// combines the values of 2 trees with the same shape (called in pre-order)
+    /// returns None if the shapes differ
+    pub fn zip_with<U, V>(&self, other : &BinTree<U>, mut f : impl FnMut(&Item, &U) -> V) -> Option<BinTree<V>> {
+        self.zip_with_rec(other, &mut f)
+    }
+    /// recursive zip_with
+    fn zip_with_rec<U, V, F : FnMut(&Item, &U) -> V>(&self, other : &BinTree<U>, f : &mut F) -> Option<BinTree<V>> {
+        match (self.node(), other.node()) {
+            (None, None) => Some(BinTree::new()),
+            (Some((value1, left1, right1)), Some((value2, left2, right2))) => {
+                let value = f(value1, value2);
+                let left = left1.zip_with_rec(left2, f)?;
+                let right = right1.zip_with_rec(right2, f)?;
+                Some(BinTree::new_node(value, left, right))
+            },
+            _ => None,
+        }
+    }
+    /// fold (catamorphism) with the accumulator threaded from the parent: a node gives
+    /// f(acc, value, left_acc, right_acc), where acc is the accumulator passed by the parent (init for the root)
+    /// and left_acc, right_acc are the folds of the branches from a clone of acc (an empty branch gives acc back)
+    pub fn fold<A : Clone>(&self, init : A, mut f : impl FnMut(A, &Item, A, A) -> A) -> A {
+        self.fold_rec(init, &mut f)
+    }
+    /// recursive fold
+    fn fold_rec<A : Clone, F : FnMut(A, &Item, A, A) -> A>(&self, acc : A, f : &mut F) -> A {
+        match self.node() {
+            None => acc,
+            Some((value, left, right)) => {
+                let left_acc = left.fold_rec(acc.clone(), f);
+                let right_acc = right.fold_rec(acc.clone(), f);
+                f(acc, value, left_acc, right_acc)
+            }
+        }
+    }
+    /// bottom-up fold without an accumulator: empty trees give a clone of init,
+    /// a node gives f(value, fold of the left branch, fold of the right branch)
+    pub fn fold_bottom_up<A : Clone>(&self, init : A, mut f : impl FnMut(&Item, A, A) -> A) -> A {
+        self.fold_bottom_up_rec(&init, &mut f)
+    }
+    /// recursive fold_bottom_up
+    fn fold_bottom_up_rec<A : Clone, F : FnMut(&Item, A, A) -> A>(&self, init : &A, f : &mut F) -> A {
+        match self.node() {
+            None => init.clone(),
+            Some((value, left, right)) => {
+                let left = left.fold_bottom_up_rec(init, f);
+                let right = right.fold_bottom_up_rec(init, f);
+                f(value, left, right)
+            }
+        }
+    }
+    /// removes the subtrees whose root is not kept (called in pre-order), heights are adjusted
+    pub fn prune(&mut self, mut keep : impl FnMut(&Item) -> bool) {
+        self.prune_rec(&mut keep);
+    }
+    /// recursive prune
+    fn prune_rec<F : FnMut(&Item) -> bool>(&mut self, keep : &mut F) {
+        let Some((value, left, right)) = self.node_mut() else { return };
+        if keep(value) {
+            left.prune_rec(keep);
+            right.prune_rec(keep);
+            self.update_height();
+        } else {
+            *self = BinTree::new();
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use crate::BinTree;
 
 impl<Item> Default for BinTree<Item> {
@@ -7,17 +8,17 @@ impl<Item> Default for BinTree<Item> {
     }
 }
 
-impl<Item : std::fmt::Debug> std::fmt::Display for BinTree<Item> {
+impl<Item : core::fmt::Debug> core::fmt::Display for BinTree<Item> {
     /// display a tree (on one line)
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.write_line(f)
     }
 }
 
 impl<Item> BinTree<Item> {
     /// display a tree on a single line with arrows indicating branches
-    pub fn write_line(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result 
-        where Item : std::fmt::Debug 
+    pub fn write_line(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result 
+        where Item : core::fmt::Debug 
     {
         if self.is_empty() {
             write!(f,"()")
@@ -37,20 +38,20 @@ impl<Item> BinTree<Item> {
         }
     }
     /// display a tree on multiple lines with a configurable tab (indent)
-    pub fn pretty_write(&self, f: &mut std::fmt::Formatter<'_>, tab: &str) -> std::fmt::Result
-        where Item : std::fmt::Debug 
+    pub fn pretty_write(&self, f: &mut core::fmt::Formatter<'_>, tab: &str) -> core::fmt::Result
+        where Item : core::fmt::Debug 
     {
         self.pretty_write_indent(f, tab, 0)
     }
-    fn pretty_write_indent(&self, f: &mut std::fmt::Formatter<'_>, tab : &str, indent : usize) -> std::fmt::Result
-        where Item : std::fmt::Debug 
+    fn pretty_write_indent(&self, f: &mut core::fmt::Formatter<'_>, tab : &str, indent : usize) -> core::fmt::Result
+        where Item : core::fmt::Debug 
     {
         if self.is_empty() {
-            write!(f,"{}{}\n",tab.repeat(indent),"@")
+            writeln!(f,"{}@",tab.repeat(indent))
         } else {
             let (value,left,right) = self.node().expect("tree should not be empty");
             right.pretty_write_indent(f, tab, indent+1)?;
-            write!(f,"{}{:?}\n",tab.repeat(indent),value)?;
+            writeln!(f,"{}{:?}",tab.repeat(indent),value)?;
             left.pretty_write_indent(f, tab, indent+1)
         }
     }
@@ -59,14 +60,14 @@ impl<Item> BinTree<Item> {
 impl<Item> BinTree<Item> {
     /// clone the contents of a tree into a vec (using default iter)
     pub fn to_vec(&self) -> Vec<Item> where Item : Clone {
-        self.iter().map(|e|e.clone()).collect()
+        self.iter().cloned().collect()
     }
 }
 
-impl<Item> Into<Vec<Item>> for BinTree<Item> {
+impl<Item> From<BinTree<Item>> for Vec<Item> {
     /// transform the tree into a vec (using default into_iter)
-    fn into(self) -> Vec<Item> {
-        self.into_iter().collect()
+    fn from(tree: BinTree<Item>) -> Self {
+        tree.into_iter().collect()
     }
 }
 
@@ -76,6 +77,24 @@ impl<Item> BinTree<Item> {
         self.iter().count()
     }
 
+    /// build a balanced tree from items that are already in order (linear time)
+    /// the in-order traversal of the tree returns the items in the same order
+    pub fn from_sorted<I>(items : I) -> Self where I : IntoIterator<Item = Item>, I::IntoIter : ExactSizeIterator {
+        let mut items = items.into_iter();
+        let len = items.len();
+        Self::from_sorted_rec(&mut items, len)
+    }
+    fn from_sorted_rec(items : &mut impl Iterator<Item = Item>, len : usize) -> Self {
+        if len == 0 {
+            Self::new()
+        } else {
+            let left = Self::from_sorted_rec(items, len / 2);
+            let value = items.next().expect("iterator should have len items");
+            let right = Self::from_sorted_rec(items, len - len / 2 - 1);
+            Self::new_node(value, left, right)
+        }
+    }
+
     /// rebalance a balanced binary tree
     pub fn rebalance(&mut self) {
         if !self.is_empty() {
@@ -97,10 +116,10 @@ impl<Item> BinTree<Item> {
     /// utility function used in rebalancing of a balanced binary tree
     pub fn rotate_right(&mut self) {
         if !self.is_empty() {
-            let mut left = std::mem::take(self.left_mut().unwrap());
-            *self.left_mut().unwrap() = std::mem::take(left.right_mut().unwrap());
+            let mut left = core::mem::take(self.left_mut().unwrap());
+            *self.left_mut().unwrap() = core::mem::take(left.right_mut().unwrap());
             self.update_height();
-            *left.right_mut().unwrap() = std::mem::take(self);
+            *left.right_mut().unwrap() = core::mem::take(self);
             *self = left;
             self.update_height();
         }
@@ -108,10 +127,10 @@ impl<Item> BinTree<Item> {
     /// utility function used in rebalancing of a balanced binary tree
     pub fn rotate_left(&mut self) {
         if !self.is_empty() {
-            let mut right = std::mem::take(self.right_mut().unwrap());
-            *self.right_mut().unwrap() = std::mem::take(right.left_mut().unwrap());
+            let mut right = core::mem::take(self.right_mut().unwrap());
+            *self.right_mut().unwrap() = core::mem::take(right.left_mut().unwrap());
             self.update_height();
-            *right.left_mut().unwrap() = std::mem::take(self);
+            *right.left_mut().unwrap() = core::mem::take(self);
             *self = right;
             self.update_height();
         }
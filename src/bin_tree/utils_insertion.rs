@@ -1,4 +1,5 @@
-use crate::BinTree;
+use alloc::vec::Vec;
+use crate::{BinTree, Result, BinTreeError, TreePath, TreeStep};
 
 impl<Item> BinTree<Item> {
 
@@ -19,6 +20,15 @@ impl<Item> BinTree<Item> {
     pub fn insert_unique(&mut self, new_item : Item) -> bool where Item : PartialOrd {
         self.insert_to_key_cmp(new_item,|i|i,Item::partial_cmp,true, true).is_none()
     }
+    /// insert into a sorted or empty tree with no duplicates and keeps both properties (rebalance)
+    /// fails with BinTreeError::DuplicateKey if the item is already present (the tree is left unchanged)
+    pub fn try_insert(&mut self, new_item : Item) -> Result<()> where Item : PartialOrd + core::fmt::Debug {
+        if self.get_sorted_to_key_cmp_checked(&new_item, |i|i, Item::partial_cmp)?.is_some() {
+            return Err(BinTreeError::duplicate_key(&new_item));
+        }
+        self.insert_to_key_cmp_checked(new_item, |i|i, Item::partial_cmp, true, true)?;
+        Ok(())
+    }
     /// extend a sorted or empty tree with no duplicates and keeps both properties (rebalance)
     pub fn extend_sorted_unique<T: IntoIterator<Item = Item>>(&mut self, iter: T) -> usize where Item : PartialOrd {
         let mut count = 0;
@@ -34,34 +44,139 @@ impl<Item> BinTree<Item> {
     /// unicity (no duplicates) optional
     /// use a function to compare keys and a function to get key from item
     /// returns the replaced item when there is a duplicate (based on compare function)
+    /// incomparable keys (compare function returns None) are treated as equal
     /// optional rebalancing
-    pub fn insert_to_key_cmp<FtoKey,Fcmp,Key>(&mut self, new_item : Item, 
+    pub fn insert_to_key_cmp<FtoKey,Fcmp,Key : ?Sized>(&mut self, new_item : Item, 
         to_key: FtoKey, cmp : Fcmp, rebalance : bool, unique : bool) -> Option<Item> where 
-        Fcmp : Fn(&Key, &Key) -> Option<std::cmp::Ordering>,
+        Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
+        FtoKey : Fn(&Item) -> &Key,
+    {
+        // nodes are allocated with new_leaf, an allocation failure aborts as for any Box
+        self.insert_rec(new_item, &to_key, &|k1,k2| cmp(k1,k2).or(Some(core::cmp::Ordering::Equal)),
+            rebalance, unique, &|item| Ok(Self::new_leaf(item)))
+            .expect("incomparable keys are treated as equal")
+    }
+
+    /// same as insert_to_key_cmp but fails with BinTreeError::Incomparable 
+    /// when the compare function returns None (the tree is left unchanged)
+    /// a key that can't be compared with itself (like NaN) is also rejected
+    /// the new node is allocated with try_new_leaf (BinTreeError::AllocationFailed)
+    pub fn insert_to_key_cmp_checked<FtoKey,Fcmp,Key : ?Sized>(&mut self, new_item : Item, 
+        to_key: FtoKey, cmp : Fcmp, rebalance : bool, unique : bool) -> Result<Option<Item>> where 
+        Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
         FtoKey : Fn(&Item) -> &Key,
+    {
+        self.insert_rec(new_item, &to_key, &cmp, rebalance, unique, &Self::try_new_leaf)
+    }
+    /// recursive insert_to_key_cmp_checked, the new node is made by leaf
+    fn insert_rec<FtoKey,Fcmp,Fleaf,Key : ?Sized>(&mut self, new_item : Item,
+        to_key: &FtoKey, cmp : &Fcmp, rebalance : bool, unique : bool, leaf : &Fleaf) -> Result<Option<Item>> where
+        Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
+        FtoKey : Fn(&Item) -> &Key,
+        Fleaf : Fn(Item) -> Result<Self>,
     {
         if self.is_empty() {
-            *self = Self::new_leaf(new_item);
-            None
+            if cmp(to_key(&new_item), to_key(&new_item)).is_none() {
+                return Err(BinTreeError::Incomparable);
+            }
+            *self = leaf(new_item)?;
+            Ok(None)
         } else {
             let mut height_adj = true;
-            // let_node_ref_mut!(self => item, left, right);
             let (item,left,right) = self.node_mut().expect("tree should not be empty");
             let result = match cmp(to_key(&new_item), to_key(item)) {
-                Some(std::cmp::Ordering::Less) => left.insert_to_key_cmp(new_item,to_key,cmp,rebalance,unique),
-                Some(std::cmp::Ordering::Greater) => right.insert_to_key_cmp(new_item,to_key,cmp,rebalance,unique),
-                _ => if unique {
+                Some(core::cmp::Ordering::Less) => left.insert_rec(new_item,to_key,cmp,rebalance,unique,leaf)?,
+                Some(core::cmp::Ordering::Greater) => right.insert_rec(new_item,to_key,cmp,rebalance,unique,leaf)?,
+                Some(core::cmp::Ordering::Equal) => if unique {
                         height_adj = false;
-                        Some(std::mem::replace(item, new_item))
+                        Some(core::mem::replace(item, new_item))
                     } else {
-                        right.insert_to_key_cmp(new_item,to_key,cmp,rebalance,unique)
-                    }
+                        right.insert_rec(new_item,to_key,cmp,rebalance,unique,leaf)?
+                    },
+                None => return Err(BinTreeError::Incomparable),
             };
             if height_adj {
-                self.height = std::cmp::max(left.height, right.height) + 1;
+                self.height = core::cmp::max(left.height, right.height) + 1;
                 if rebalance { self.rebalance(); }
             }
-            result
+            Ok(result)
+        }
+    }
+
+    /// mutable ref to the item with a key in a sorted or empty tree, made from the key if missing
+    /// one descent: incomparable keys are treated as equal (as in insert_to_key_cmp)
+    /// heights are adjusted, optional rebalancing
+    pub fn get_or_insert_to_key_cmp<FtoKey,Fcmp,Fmake,Key>(&mut self, key : Key, make : Fmake,
+        to_key: FtoKey, cmp : Fcmp, rebalance : bool) -> &mut Item where
+        Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
+        FtoKey : Fn(&Item) -> &Key,
+        Fmake : FnOnce(Key) -> Item,
+    {
+        let path = self.get_or_insert_path_to_key_cmp(key, make, to_key, cmp, rebalance);
+        self.value_at_mut(&path).expect("path leads to the item")
+    }
+    /// same as get_or_insert_to_key_cmp but returns the path to the item (valid until the tree is changed)
+    pub fn get_or_insert_path_to_key_cmp<FtoKey,Fcmp,Fmake,Key>(&mut self, key : Key, make : Fmake,
+        to_key: FtoKey, cmp : Fcmp, rebalance : bool) -> TreePath where
+        Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
+        FtoKey : Fn(&Item) -> &Key,
+        Fmake : FnOnce(Key) -> Item,
+    {
+        let mut steps = Vec::new();
+        self.get_or_insert_rec(key, make, &to_key, &cmp, rebalance, &mut steps);
+        steps.into_iter().rev().collect()
+    }
+    /// recursive get_or_insert_to_key_cmp, records the steps from the item up to self
+    fn get_or_insert_rec<FtoKey,Fcmp,Fmake,Key>(&mut self, key : Key, make : Fmake,
+        to_key: &FtoKey, cmp : &Fcmp, rebalance : bool, steps : &mut Vec<TreeStep>) where
+        Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
+        FtoKey : Fn(&Item) -> &Key,
+        Fmake : FnOnce(Key) -> Item,
+    {
+        let Some((item, left, right)) = self.node_mut() else {
+            *self = Self::new_leaf(make(key));
+            return;
+        };
+        let (step, branch) = match cmp(&key, to_key(item)) {
+            Some(core::cmp::Ordering::Less) => (TreeStep::Left, left),
+            Some(core::cmp::Ordering::Greater) => (TreeStep::Right, right),
+            _ => return,
+        };
+        branch.get_or_insert_rec(key, make, to_key, cmp, rebalance, steps);
+        steps.push(step);
+        self.update_height();
+        if rebalance { self.rebalance_tracking(steps); }
+    }
+    /// same rotations as rebalance, the steps from a node up to self are adjusted to follow it
+    fn rebalance_tracking(&mut self, steps : &mut Vec<TreeStep>) {
+        if self.balance() > 1 {
+            let left_balance = self.left().expect("tree should not be empty").balance();
+            if left_balance >= 0 {
+                self.rotate_right();
+                rotated(steps, TreeStep::Left);
+            } else if left_balance == -1 {
+                self.rotate_left_right();
+                if steps.last() == Some(&TreeStep::Left) {
+                    steps.pop();
+                    rotated(steps, TreeStep::Right);
+                    steps.push(TreeStep::Left);
+                }
+                rotated(steps, TreeStep::Left);
+            }
+        } else if self.balance() < -1 {
+            let right_balance = self.right().expect("tree should not be empty").balance();
+            if right_balance <= 0 {
+                self.rotate_left();
+                rotated(steps, TreeStep::Right);
+            } else if right_balance == 1 {
+                self.rotate_right_left();
+                if steps.last() == Some(&TreeStep::Right) {
+                    steps.pop();
+                    rotated(steps, TreeStep::Left);
+                    steps.push(TreeStep::Right);
+                }
+                rotated(steps, TreeStep::Right);
+            }
         }
     }
 
@@ -69,6 +184,7 @@ impl<Item> BinTree<Item> {
     pub fn push_right(&mut self, new_item : Item) {
         if let Some(right) = self.right_mut() {
             right.push_right(new_item);
+            self.update_height();
         } else {
             // empty
             *self = Self::new_leaf(new_item)
@@ -84,7 +200,8 @@ impl<Item> BinTree<Item> {
     /// push to the left branch of a tree (linear tree)
     pub fn push_left(&mut self, new_item : Item) {
         if let Some(left) = self.left_mut() {
-                left.push_left(new_item);
+            left.push_left(new_item);
+            self.update_height();
         } else {
             // empty
             *self = Self::new_leaf(new_item)
@@ -97,4 +214,26 @@ impl<Item> BinTree<Item> {
         }
     
     }
-}
\ No newline at end of file
+}
+
+/// adjusts the steps from a node up to the root of a rotation (last step first)
+/// the branch on the up side became the root, the old root its branch on the other side
+fn rotated(steps : &mut Vec<TreeStep>, up : TreeStep) {
+    let down = match up {
+        TreeStep::Left => TreeStep::Right,
+        TreeStep::Right => TreeStep::Left,
+    };
+    if steps.last() != Some(&up) {
+        // the old root or its other branch goes one level down
+        steps.push(down);
+    } else {
+        // the up branch and its outer branch go one level up
+        steps.pop();
+        if steps.last() == Some(&down) {
+            // its inner branch moves under the old root
+            steps.pop();
+            steps.push(up);
+            steps.push(down);
+        }
+    }
+}
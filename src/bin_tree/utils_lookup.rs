@@ -1,39 +1,67 @@
-use crate::BinTree;
+use crate::{BinTree, Result, BinTreeError};
 
 impl<Item> BinTree<Item> {
 
         /// find a value in a sorted tree with key and compare functions and return ref
-        pub fn get_sorted_to_key_cmp<FtoKey,Fcmp,Key>(&self, target_key : &Key,
+        /// incomparable keys (compare function returns None) are treated as equal
+        pub fn get_sorted_to_key_cmp<FtoKey,Fcmp,Key : ?Sized>(&self, target_key : &Key,
             to_key: FtoKey, cmp : Fcmp) -> Option<&Item> where 
-            Fcmp : Fn(&Key, &Key) -> Option<std::cmp::Ordering>,
+            Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
+            FtoKey : Fn(&Item) -> &Key,
+        {
+            self.get_sorted_to_key_cmp_checked(target_key, to_key, 
+                |k1,k2| cmp(k1,k2).or(Some(core::cmp::Ordering::Equal)))
+                .expect("incomparable keys are treated as equal")
+        }
+
+        /// same as get_sorted_to_key_cmp but fails with BinTreeError::Incomparable
+        /// when the compare function returns None
+        pub fn get_sorted_to_key_cmp_checked<FtoKey,Fcmp,Key : ?Sized>(&self, target_key : &Key,
+            to_key: FtoKey, cmp : Fcmp) -> Result<Option<&Item>> where 
+            Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
             FtoKey : Fn(&Item) -> &Key,
         {
             if self.is_empty() {
-                None
+                Ok(None)
             } else {
                 let (value,left,right) = self.node().expect("tree should not be empty");
                 match cmp(target_key, to_key(value)) {
-                    Some(std::cmp::Ordering::Less) => left.get_sorted_to_key_cmp(target_key,to_key,cmp),
-                    Some(std::cmp::Ordering::Greater) => right.get_sorted_to_key_cmp(target_key,to_key,cmp),
-                    _ => Some(value),
+                    Some(core::cmp::Ordering::Less) => left.get_sorted_to_key_cmp_checked(target_key,to_key,cmp),
+                    Some(core::cmp::Ordering::Greater) => right.get_sorted_to_key_cmp_checked(target_key,to_key,cmp),
+                    Some(core::cmp::Ordering::Equal) => Ok(Some(value)),
+                    None => Err(BinTreeError::Incomparable),
                 }
             }
         }
     
         /// find a value in a sorted tree with key and compare functions and return mut ref
-        pub fn get_mut_sorted_to_key_cmp<FtoKey,Fcmp,Key>(&mut self, target_key : &Key,
+        /// incomparable keys (compare function returns None) are treated as equal
+        pub fn get_mut_sorted_to_key_cmp<FtoKey,Fcmp,Key : ?Sized>(&mut self, target_key : &Key,
             to_key: FtoKey, cmp : Fcmp) -> Option<&mut Item> where 
-            Fcmp : Fn(&Key, &Key) -> Option<std::cmp::Ordering>,
+            Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
+            FtoKey : Fn(&Item) -> &Key,
+        {
+            self.get_mut_sorted_to_key_cmp_checked(target_key, to_key, 
+                |k1,k2| cmp(k1,k2).or(Some(core::cmp::Ordering::Equal)))
+                .expect("incomparable keys are treated as equal")
+        }
+
+        /// same as get_mut_sorted_to_key_cmp but fails with BinTreeError::Incomparable
+        /// when the compare function returns None
+        pub fn get_mut_sorted_to_key_cmp_checked<FtoKey,Fcmp,Key : ?Sized>(&mut self, target_key : &Key,
+            to_key: FtoKey, cmp : Fcmp) -> Result<Option<&mut Item>> where 
+            Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
             FtoKey : Fn(&Item) -> &Key,
         {
             if self.is_empty() {
-                None
+                Ok(None)
             } else {
                 let (value,left,right) = self.node_mut().expect("tree should not be empty");
                 match cmp(target_key, to_key(value)) {
-                    Some(std::cmp::Ordering::Less) => left.get_mut_sorted_to_key_cmp(target_key,to_key,cmp),
-                    Some(std::cmp::Ordering::Greater) => right.get_mut_sorted_to_key_cmp(target_key,to_key,cmp),
-                    _ => Some(value),
+                    Some(core::cmp::Ordering::Less) => left.get_mut_sorted_to_key_cmp_checked(target_key,to_key,cmp),
+                    Some(core::cmp::Ordering::Greater) => right.get_mut_sorted_to_key_cmp_checked(target_key,to_key,cmp),
+                    Some(core::cmp::Ordering::Equal) => Ok(Some(value)),
+                    None => Err(BinTreeError::Incomparable),
                 }
             }
         }
@@ -49,17 +77,17 @@ impl<Item> BinTree<Item> {
         }
 
         /// find a value in a sorted tree with key and compare functions and return mut ref
-        pub fn get_tree_mut_sorted_to_key_cmp<FtoKey,Fcmp,Key>(&mut self, target_key : &Key,
+        pub fn get_tree_mut_sorted_to_key_cmp<FtoKey,Fcmp,Key : ?Sized>(&mut self, target_key : &Key,
             to_key: FtoKey, cmp : Fcmp) -> Option<&mut BinTree<Item>> where 
-            Fcmp : Fn(&Key, &Key) -> Option<std::cmp::Ordering>,
+            Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
             FtoKey : Fn(&Item) -> &Key,
         {
             if self.is_empty() {
                 None
             } else {
                 match cmp(target_key, to_key(self.value().unwrap())) {
-                    Some(std::cmp::Ordering::Less) => self.left_mut().unwrap().get_tree_mut_sorted_to_key_cmp(target_key,to_key,cmp),
-                    Some(std::cmp::Ordering::Greater) => self.right_mut().unwrap().get_tree_mut_sorted_to_key_cmp(target_key,to_key,cmp),
+                    Some(core::cmp::Ordering::Less) => self.left_mut().unwrap().get_tree_mut_sorted_to_key_cmp(target_key,to_key,cmp),
+                    Some(core::cmp::Ordering::Greater) => self.right_mut().unwrap().get_tree_mut_sorted_to_key_cmp(target_key,to_key,cmp),
                     _ => Some(self),
                 }
             }
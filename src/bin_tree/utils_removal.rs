@@ -1,9 +1,9 @@
-use crate::BinTree;
+use crate::{BinTree, Result, BinTreeError};
 
 impl<Item> BinTree<Item> {
 
     /// try to remove value from a tree
-    /// heights are not adjusted
+    /// heights are adjusted
     pub fn remove(&mut self, target_value : &Item) -> Option<Item> where Item : PartialEq {
         if self.is_empty() {
             None
@@ -17,40 +17,70 @@ impl<Item> BinTree<Item> {
                 if r.is_none() {
                     r = right.remove(target_value);
                 }
+                if r.is_some() {
+                    self.update_height();
+                }
                 r
             }
         }
     }
 
+    /// remove value from a tree
+    /// fails with BinTreeError::KeyNotFound if the value is not in the tree
+    /// heights are adjusted
+    pub fn try_remove(&mut self, target_value : &Item) -> Result<Item> where Item : PartialEq + core::fmt::Debug {
+        self.remove(target_value).ok_or_else(|| BinTreeError::key_not_found(target_value))
+    }
+
     /// try to remove value from a sorted tree and preserve order
     pub fn remove_sorted(&mut self, target_value : &Item) -> Option<Item> where Item : PartialOrd {
         self.remove_sorted_to_key_cmp(target_value, |x|x, Item::partial_cmp, true)
     }
 
+    /// remove value from a sorted tree and preserve order
+    /// fails with BinTreeError::KeyNotFound if the value is not in the tree
+    pub fn try_remove_sorted(&mut self, target_value : &Item) -> Result<Item> where Item : PartialOrd + core::fmt::Debug {
+        self.remove_sorted_to_key_cmp_checked(target_value, |x|x, Item::partial_cmp, true)?
+            .ok_or_else(|| BinTreeError::key_not_found(target_value))
+    }
+
     /// try to remove sorted tree and preserve order
     /// uses key and compare functions
+    /// incomparable keys (compare function returns None) are treated as equal
     /// optional rebalancing
     /// heights are preserved
-    pub fn remove_sorted_to_key_cmp<FtoKey,Fcmp,Key>(&mut self, target_key : &Key, 
+    pub fn remove_sorted_to_key_cmp<FtoKey,Fcmp,Key : ?Sized>(&mut self, target_key : &Key, 
         to_key: FtoKey, cmp : Fcmp, rebalance : bool) -> Option<Item> where 
-        Fcmp : Fn(&Key, &Key) -> Option<std::cmp::Ordering>,
+        Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
+        FtoKey : Fn(&Item) -> &Key,
+    {
+        self.remove_sorted_to_key_cmp_checked(target_key, to_key, 
+            |k1,k2| cmp(k1,k2).or(Some(core::cmp::Ordering::Equal)), rebalance)
+            .expect("incomparable keys are treated as equal")
+    }
+
+    /// same as remove_sorted_to_key_cmp but fails with BinTreeError::Incomparable
+    /// when the compare function returns None (the tree is left unchanged)
+    pub fn remove_sorted_to_key_cmp_checked<FtoKey,Fcmp,Key : ?Sized>(&mut self, target_key : &Key, 
+        to_key: FtoKey, cmp : Fcmp, rebalance : bool) -> Result<Option<Item>> where 
+        Fcmp : Fn(&Key, &Key) -> Option<core::cmp::Ordering>,
         FtoKey : Fn(&Item) -> &Key,
     {
         if self.is_empty() {
-            None
+            Ok(None)
         } else {
             let (value,left,right) = self.node_mut().expect("tree should not be empty");
-            // let_node_ref_mut!(self => value, left, right);
             let result = match cmp(target_key, to_key(value)) {
-                Some(std::cmp::Ordering::Less) => left.remove_sorted_to_key_cmp(target_key,to_key,cmp,rebalance),
-                Some(std::cmp::Ordering::Greater) => right.remove_sorted_to_key_cmp(target_key,to_key,cmp,rebalance),
-                _ => self.pop_sorted(rebalance),
+                Some(core::cmp::Ordering::Less) => left.remove_sorted_to_key_cmp_checked(target_key,to_key,cmp,rebalance)?,
+                Some(core::cmp::Ordering::Greater) => right.remove_sorted_to_key_cmp_checked(target_key,to_key,cmp,rebalance)?,
+                Some(core::cmp::Ordering::Equal) => self.pop_sorted(rebalance),
+                None => return Err(BinTreeError::Incomparable),
             };
             if result.is_some() {
                 self.update_height();
                 if rebalance { self.rebalance(); }
             }
-            result
+            Ok(result)
         }
     }
 
@@ -62,19 +92,21 @@ impl<Item> BinTree<Item> {
             None
         } else {
             if self.left().unwrap().is_empty() && self.right().unwrap().is_empty() {
-                Some(std::mem::take(self))
+                Some(core::mem::take(self))
             } else if self.right().unwrap().is_empty() {
-                let left = std::mem::take(self.left_mut().unwrap());
-                Some(std::mem::replace(self, left))
+                let left = core::mem::take(self.left_mut().unwrap());
+                Some(core::mem::replace(self, left))
             } else if self.left().unwrap().is_empty() {
-                let right = std::mem::take(self.right_mut().unwrap());
-                Some(std::mem::replace(self, right))
+                let right = core::mem::take(self.right_mut().unwrap());
+                Some(core::mem::replace(self, right))
             } else {
                 let mut new_self = self.right_mut().unwrap().detach_left_min_tree_sorted(rebalance).unwrap();
-                std::mem::swap(self.left_mut().unwrap(), new_self.left_mut().unwrap());
-                std::mem::swap(self.right_mut().unwrap(), new_self.right_mut().unwrap());
-                std::mem::swap(self, &mut new_self);
+                core::mem::swap(self.left_mut().unwrap(), new_self.left_mut().unwrap());
+                core::mem::swap(self.right_mut().unwrap(), new_self.right_mut().unwrap());
+                core::mem::swap(self, &mut new_self);
                 self.update_height();
+                // the right branch may be shorter now
+                if rebalance { self.rebalance() };
                 Some(new_self)
             }
         }
@@ -85,12 +117,12 @@ impl<Item> BinTree<Item> {
     /// the tree is adjusted on the way up, including heights and optional rebalancing
     pub fn detach_left_min_tree_sorted(&mut self, rebalance : bool) -> Option<BinTree<Item>> {
         if self.is_leaf() {
-            Some(std::mem::take(self))
+            Some(core::mem::take(self))
         } else if self.is_branch() {
             if self.left().unwrap().is_empty() {
                 // no left path
-                let right = std::mem::take(self.right_mut().unwrap());
-                Some(std::mem::replace(self, right))
+                let right = core::mem::take(self.right_mut().unwrap());
+                Some(core::mem::replace(self, right))
             } else {
                 // min from left path
                 let result = self.left_mut().unwrap().detach_left_min_tree_sorted(rebalance);
@@ -103,8 +135,45 @@ impl<Item> BinTree<Item> {
         }
     }
 
+    /// detaches the mutable tree node containing the maximum value item
+    /// assumes that the tree is sorted
+    /// the tree is adjusted on the way up, including heights and optional rebalancing
+    pub fn detach_right_max_tree_sorted(&mut self, rebalance : bool) -> Option<BinTree<Item>> {
+        if self.is_leaf() {
+            Some(core::mem::take(self))
+        } else if self.is_branch() {
+            if self.right().unwrap().is_empty() {
+                // no right path
+                let left = core::mem::take(self.left_mut().unwrap());
+                Some(core::mem::replace(self, left))
+            } else {
+                // max from right path
+                let result = self.right_mut().unwrap().detach_right_max_tree_sorted(rebalance);
+                self.update_height();
+                if rebalance { self.rebalance() };
+                result
+            }
+        } else {
+            None
+        }
+    }
+
+    /// pop the minimum value from a sorted tree and preserves order
+    /// heights are adjusted
+    /// rebalancing is optional
+    pub fn pop_min_sorted(&mut self, rebalance : bool) -> Option<Item> {
+        self.detach_left_min_tree_sorted(rebalance).and_then(BinTree::into_value)
+    }
+
+    /// pop the maximum value from a sorted tree and preserves order
+    /// heights are adjusted
+    /// rebalancing is optional
+    pub fn pop_max_sorted(&mut self, rebalance : bool) -> Option<Item> {
+        self.detach_right_max_tree_sorted(rebalance).and_then(BinTree::into_value)
+    }
+
     /// pop the top node from the tree
-    /// heights are not adjusted
+    /// heights are adjusted
     pub fn pop_tree(&mut self) -> Option<BinTree<Item>> {
         if self.is_empty() {
             None
@@ -116,12 +185,14 @@ impl<Item> BinTree<Item> {
             }
             Some(match p {
                 None => {
-                    std::mem::take(self)
+                    core::mem::take(self)
                 },
                 Some(mut new_self) => {
-                    std::mem::swap(self.left_mut().unwrap(), new_self.left_mut().unwrap());
-                    std::mem::swap(self.right_mut().unwrap(), new_self.right_mut().unwrap());
-                    std::mem::swap(self, &mut new_self);
+                    core::mem::swap(self.left_mut().unwrap(), new_self.left_mut().unwrap());
+                    core::mem::swap(self.right_mut().unwrap(), new_self.right_mut().unwrap());
+                    core::mem::swap(self, &mut new_self);
+                    self.update_height();
+                    new_self.update_height();
                     new_self
                 },
             })
@@ -129,7 +200,7 @@ impl<Item> BinTree<Item> {
     }
 
     /// pop the top item from the tree
-    /// heights are not adjusted
+    /// heights are adjusted
     pub fn pop(&mut self) -> Option<Item> {
         let pop_tree = self.pop_tree();
         if let Some(pop_tree) = pop_tree {
@@ -151,6 +222,7 @@ impl<Item> BinTree<Item> {
     }
 
     /// pop from the left of tree
+    /// heights are adjusted
     pub fn pop_left(&mut self) -> Option<Item> {
         if self.is_empty() {
             None
@@ -158,16 +230,19 @@ impl<Item> BinTree<Item> {
             let (_,left,right) = self.node_mut().expect("tree should not be empty");
             // let_node_ref_mut!(self => _value, left, right);
             if left.is_empty() && right.is_empty() {
-                std::mem::take(self).into_value()
+                core::mem::take(self).into_value()
             } else if left.is_empty() {
                 self.pop()
             } else {
-                left.pop_left()
+                let popped = left.pop_left();
+                self.update_height();
+                popped
             }
         }
     }
 
     /// pop from the right of tree
+    /// heights are adjusted
     pub fn pop_right(&mut self) -> Option<Item> {
         if self.is_empty() {
             None
@@ -175,11 +250,13 @@ impl<Item> BinTree<Item> {
             let (_,left,right) = self.node_mut().expect("tree should not be empty");
             // let_node_ref_mut!(self => _value, left, right);
             if left.is_empty() && right.is_empty() {
-                std::mem::take(self).into_value()
+                core::mem::take(self).into_value()
             } else if right.is_empty() {
                 self.pop()
             } else {
-                right.pop_right()
+                let popped = right.pop_right();
+                self.update_height();
+                popped
             }
         }
     }
@@ -0,0 +1,165 @@
+use alloc::{vec, vec::Vec};
+use crate::{BinTree, BinTreeIntoIter};
+
+impl<Item> BinTree<Item> {
+    /// keep only the items for which the predicate returns true (called in order)
+    /// the in-order sequence is preserved, a balanced tree stays balanced (the branches are joined
+    /// and rebalanced on the way up), otherwise the tree keeps its shape: a removed node is replaced
+    /// by its only branch, or by the first item of its right branch
+    pub fn retain<F>(&mut self, mut pred : F) where F : FnMut(&Item) -> bool {
+        self.extract_if(|item| !pred(item)).for_each(drop);
+    }
+
+    /// lazy iterator that removes and returns the items for which the predicate returns true
+    /// items are visited in order, removed nodes are replaced as in retain (balanced or same shape)
+    /// the tree is put back together when the iterator is dropped, items not yet visited are kept
+    pub fn extract_if<F>(&mut self, pred : F) -> BinTreeExtractIf<'_, Item, F> where F : FnMut(&mut Item) -> bool {
+        let root = core::mem::take(self);
+        let frames = Vec::with_capacity(usize::try_from(root.height()).unwrap_or(0));
+        let balanced = root.is_balanced();
+        let mut iter = BinTreeExtractIf { tree: self, frames, done: BinTree::new(), balanced, pred };
+        iter.push_left(root);
+        iter
+    }
+}
+
+/// a node taken apart by BinTreeExtractIf
+struct Frame<Item> {
+    /// None once extracted
+    value: Option<Item>,
+    /// None while the left branch is walked
+    left: Option<BinTree<Item>>,
+    /// the right branch until it is walked
+    right: BinTree<Item>,
+}
+
+/// puts a walked node back together
+/// an extracted value is replaced by the only branch or by the first item of the right branch
+fn join<Item>(value : Option<Item>, left : BinTree<Item>, mut right : BinTree<Item>, balanced : bool) -> BinTree<Item> {
+    let value = match value {
+        Some(value) => value,
+        None if left.is_empty() => return right,
+        None if right.is_empty() => return left,
+        // structural, the branch doesn't need to be sorted
+        None => right.pop_min_sorted(balanced).expect("right branch is not empty"),
+    };
+    if balanced { join_balanced(left, value, right) } else { BinTree::new_node(value, left, right) }
+}
+
+/// node made of 2 balanced trees of any heights (AVL join): the taller tree is walked down
+/// on the side of the shorter one, and rebalanced on the way up
+fn join_balanced<Item>(left : BinTree<Item>, value : Item, right : BinTree<Item>) -> BinTree<Item> {
+    let mut tree = if left.height() > right.height() + 1 {
+        let (left_value, left_left, left_right) = left.into_node().expect("taller tree is not empty");
+        BinTree::new_node(left_value, left_left, join_balanced(left_right, value, right))
+    } else if right.height() > left.height() + 1 {
+        let (right_value, right_left, right_right) = right.into_node().expect("taller tree is not empty");
+        BinTree::new_node(right_value, join_balanced(left, value, right_left), right_right)
+    } else {
+        return BinTree::new_node(value, left, right);
+    };
+    tree.rebalance();
+    tree
+}
+
+/// iterator returned by BinTree::extract_if
+/// the ancestors of the walked branch are kept apart until the iterator is dropped
+pub struct BinTreeExtractIf<'a, Item, F> where F : FnMut(&mut Item) -> bool {
+    tree: &'a mut BinTree<Item>,
+    frames: Vec<Frame<Item>>,
+    /// the last walked branch, put back together
+    done: BinTree<Item>,
+    /// the tree was balanced, branches are joined with join_balanced
+    balanced: bool,
+    pred: F,
+}
+
+impl<Item, F> BinTreeExtractIf<'_, Item, F> where F : FnMut(&mut Item) -> bool {
+    /// takes apart the left spine of a tree
+    fn push_left(&mut self, mut tree : BinTree<Item>) {
+        while let Some((value, left, right)) = tree.into_node() {
+            self.frames.push(Frame { value: Some(value), left: None, right });
+            tree = left;
+        }
+    }
+}
+
+impl<Item, F> Iterator for BinTreeExtractIf<'_, Item, F> where F : FnMut(&mut Item) -> bool {
+    type Item = Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.frames.last_mut()?;
+            if frame.left.is_none() {
+                // left branch walked: visit the value, then walk the right branch
+                frame.left = Some(core::mem::take(&mut self.done));
+                let extracted = if frame.value.as_mut().is_some_and(&mut self.pred) { frame.value.take() } else { None };
+                let right = core::mem::take(&mut frame.right);
+                self.push_left(right);
+                if extracted.is_some() {
+                    return extracted;
+                }
+            } else {
+                // right branch walked
+                let frame = self.frames.pop().expect("not empty");
+                let right = core::mem::take(&mut self.done);
+                self.done = join(frame.value, frame.left.expect("left branch walked"), right, self.balanced);
+            }
+        }
+    }
+}
+
+impl<Item, F> Drop for BinTreeExtractIf<'_, Item, F> where F : FnMut(&mut Item) -> bool {
+    fn drop(&mut self) {
+        while let Some(frame) = self.frames.pop() {
+            let (left, right) = match frame.left {
+                None => (core::mem::take(&mut self.done), frame.right),
+                // the right branch is still in the frame if the predicate panicked
+                Some(left) if !frame.right.is_empty() => (left, frame.right),
+                Some(left) => (left, core::mem::take(&mut self.done)),
+            };
+            self.done = join(frame.value, left, right, self.balanced);
+        }
+        *self.tree = core::mem::take(&mut self.done);
+    }
+}
+
+/// state shared by the extract_if iterators of the map and set
+/// the tree is rebuilt balanced from the kept items on drop (linear time)
+/// the length is 0 until then, so a leaked iterator leaves an empty but consistent container
+pub(crate) struct ExtractState<'a, Item> {
+    tree: &'a mut BinTree<Item>,
+    len: &'a mut usize,
+    items: BinTreeIntoIter<Item>,
+    kept: Vec<Item>,
+    /// the item given to the predicate, kept on drop if the predicate panicked
+    current: Option<Item>,
+}
+
+impl<'a, Item> ExtractState<'a, Item> {
+    pub(crate) fn new(tree : &'a mut BinTree<Item>, len : &'a mut usize) -> Self {
+        let items = core::mem::take(&mut *tree).into_iter_dfs_in();
+        *len = 0;
+        Self { tree, len, items, kept: vec![], current: None }
+    }
+    /// next item for which the predicate returns true (other items are kept)
+    pub(crate) fn next_with<F>(&mut self, mut pred : F) -> Option<Item> where F : FnMut(&mut Item) -> bool {
+        for item in self.items.by_ref() {
+            if pred(self.current.insert(item)) {
+                return self.current.take();
+            }
+            self.kept.extend(self.current.take());
+        }
+        None
+    }
+}
+
+impl<Item> Drop for ExtractState<'_, Item> {
+    fn drop(&mut self) {
+        let mut kept = core::mem::take(&mut self.kept);
+        kept.extend(self.current.take());
+        kept.extend(self.items.by_ref());
+        *self.len = kept.len();
+        *self.tree = BinTree::from_sorted(kept);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{BinTree, Result, BinTreeError};
+use alloc::boxed::Box;
+use crate::{BinTree, BinTreeNode, Result, BinTreeError, bin_tree::HashCache};
 
 impl<Item: PartialEq> BinTree<Item> {
     /// swap 2 items in the tree (lookup by value)
@@ -11,11 +12,49 @@ impl<Item: PartialEq> BinTree<Item> {
         let ptr2 = opt2.unwrap() as * mut Item;
         if ptr1 != ptr2 {
             unsafe {
-                std::ptr::swap(ptr1,ptr2);
+                core::ptr::swap(ptr1,ptr2);
             }
             Ok(())
         } else {
             Err(BinTreeError::SwapSame)
         }
     }
+    /// swap 2 items in the tree (lookup by value)
+    /// same as swap but a missing item is reported as BinTreeError::KeyNotFound
+    pub fn try_swap(&mut self, value1 : &Item, value2 : &Item) -> Result<()> where Item : core::fmt::Debug {
+        self.swap(value1, value2).map_err(|e| e.with_swap_keys(value1, value2))
+    }
+}
+
+impl<Item> BinTree<Item> {
+    /// creates a node without aborting when the allocation fails
+    /// fails with BinTreeError::AllocationFailed
+    pub fn try_new_node(value : Item, left: BinTree<Item>, right: BinTree<Item>) -> Result<Self> {
+        let height = core::cmp::max(left.height(),right.height()) + 1;
+        Ok(Self { 
+            root : Some(try_box(BinTreeNode{value, left, right, hash: HashCache::new()})?), 
+            height,
+        })
+    }
+    /// creates a leaf without aborting when the allocation fails
+    /// fails with BinTreeError::AllocationFailed
+    pub fn try_new_leaf(item : Item) -> Result<Self> {
+        Self::try_new_node(item, Self::new(), Self::new())
+    }
+}
+
+/// fallible version of Box::new (Box::try_new is not stable)
+fn try_box<T>(value : T) -> Result<Box<T>> {
+    let layout = alloc::alloc::Layout::new::<T>();
+    if layout.size() == 0 {
+        return Ok(Box::new(value));
+    }
+    let ptr = unsafe { alloc::alloc::alloc(layout) } as * mut T;
+    if ptr.is_null() {
+        return Err(BinTreeError::AllocationFailed { bytes: layout.size() });
+    }
+    unsafe {
+        ptr.write(value);
+        Ok(Box::from_raw(ptr))
+    }
 }
@@ -0,0 +1,99 @@
+use crate::BinTree;
+
+/// what a walk does after a visitor callback
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum VisitControl {
+    /// keep walking
+    Continue,
+    /// do not walk the remaining branches of the current node
+    /// (both branches from enter_node, the right branch from visit_value, same as Continue elsewhere)
+    SkipChildren,
+    /// end the walk
+    Stop,
+}
+
+use VisitControl::*;
+
+/// callbacks of a walk over a tree, in depth-first order (depth is 0 for the root)
+/// for a node: enter_node, walk of the left branch, visit_value, walk of the right branch, leave_node
+pub trait BinTreeVisitor<Item> {
+    /// called before the branches of a node
+    fn enter_node(&mut self, _value : &Item, _depth : usize) -> VisitControl {
+        Continue
+    }
+    /// called between the left and the right branches of a node
+    fn visit_value(&mut self, _value : &Item, _depth : usize) -> VisitControl {
+        Continue
+    }
+    /// called after the branches of a node
+    fn leave_node(&mut self, _value : &Item, _depth : usize) -> VisitControl {
+        Continue
+    }
+    /// called for each empty branch
+    fn visit_empty(&mut self, _depth : usize) -> VisitControl {
+        Continue
+    }
+}
+
+/// callbacks of a walk over a tree with mutable values (see BinTreeVisitor)
+pub trait BinTreeVisitorMut<Item> {
+    /// called before the branches of a node
+    fn enter_node(&mut self, _value : &mut Item, _depth : usize) -> VisitControl {
+        Continue
+    }
+    /// called between the left and the right branches of a node
+    fn visit_value(&mut self, _value : &mut Item, _depth : usize) -> VisitControl {
+        Continue
+    }
+    /// called after the branches of a node
+    fn leave_node(&mut self, _value : &mut Item, _depth : usize) -> VisitControl {
+        Continue
+    }
+    /// called for each empty branch
+    fn visit_empty(&mut self, _depth : usize) -> VisitControl {
+        Continue
+    }
+}
+
+impl<Item> BinTree<Item> {
+    /// walks the tree with a visitor, returns Stop if the visitor stopped the walk (Continue otherwise)
+    pub fn walk(&self, visitor : &mut impl BinTreeVisitor<Item>) -> VisitControl {
+        self.walk_rec(visitor, 0)
+    }
+    /// recursive walk
+    fn walk_rec(&self, visitor : &mut impl BinTreeVisitor<Item>, depth : usize) -> VisitControl {
+        let Some((value, left, right)) = self.node() else {
+            return stop_or_continue(visitor.visit_empty(depth));
+        };
+        let enter = visitor.enter_node(value, depth);
+        if enter == Stop { return Stop }
+        if enter == Continue && left.walk_rec(visitor, depth + 1) == Stop { return Stop }
+        let visit = visitor.visit_value(value, depth);
+        if visit == Stop { return Stop }
+        if enter == Continue && visit == Continue && right.walk_rec(visitor, depth + 1) == Stop { return Stop }
+        stop_or_continue(visitor.leave_node(value, depth))
+    }
+    /// walks the tree with a visitor that can modify the values (not the shape)
+    /// returns Stop if the visitor stopped the walk (Continue otherwise)
+    pub fn walk_mut(&mut self, visitor : &mut impl BinTreeVisitorMut<Item>) -> VisitControl {
+        self.walk_mut_rec(visitor, 0)
+    }
+    /// recursive walk
+    fn walk_mut_rec(&mut self, visitor : &mut impl BinTreeVisitorMut<Item>, depth : usize) -> VisitControl {
+        let Some((value, left, right)) = self.node_mut() else {
+            return stop_or_continue(visitor.visit_empty(depth));
+        };
+        let enter = visitor.enter_node(value, depth);
+        if enter == Stop { return Stop }
+        if enter == Continue && left.walk_mut_rec(visitor, depth + 1) == Stop { return Stop }
+        let visit = visitor.visit_value(value, depth);
+        if visit == Stop { return Stop }
+        if enter == Continue && visit == Continue && right.walk_mut_rec(visitor, depth + 1) == Stop { return Stop }
+        stop_or_continue(visitor.leave_node(value, depth))
+    }
+}
+
+/// SkipChildren has no effect after the branches
+fn stop_or_continue(control : VisitControl) -> VisitControl {
+    if control == Stop { Stop } else { Continue }
+}
@@ -0,0 +1,103 @@
+use core::cmp::Ordering;
+
+/// an ordering on keys carried by the ordered containers (BinTreeMap, BinTreeOrderedSet)
+/// the container always inserts and looks up with the same comparator
+/// None means that the keys can't be compared (like partial_cmp)
+pub trait Comparator<Key : ?Sized> {
+    fn compare(&self, a: &Key, b: &Key) -> Option<Ordering>;
+}
+
+/// natural order of the keys (uses partial_cmp)
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct NaturalOrder;
+
+impl<Key : PartialOrd + ?Sized> Comparator<Key> for NaturalOrder {
+    fn compare(&self, a: &Key, b: &Key) -> Option<Ordering> {
+        a.partial_cmp(b)
+    }
+}
+
+/// total order of the keys (uses Ord::cmp), keys are never incomparable
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct TotalOrder;
+
+impl<Key : Ord + ?Sized> Comparator<Key> for TotalOrder {
+    fn compare(&self, a: &Key, b: &Key) -> Option<Ordering> {
+        Some(a.cmp(b))
+    }
+}
+
+/// total order for float keys (uses total_cmp: NaN is ordered after infinity)
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct FloatTotalOrder;
+
+impl Comparator<f64> for FloatTotalOrder {
+    fn compare(&self, a: &f64, b: &f64) -> Option<Ordering> {
+        Some(a.total_cmp(b))
+    }
+}
+
+impl Comparator<f32> for FloatTotalOrder {
+    fn compare(&self, a: &f32, b: &f32) -> Option<Ordering> {
+        Some(a.total_cmp(b))
+    }
+}
+
+/// reverses the order of another comparator
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct ReverseOrder<C = NaturalOrder>(pub C);
+
+impl<Key : ?Sized, C : Comparator<Key>> Comparator<Key> for ReverseOrder<C> {
+    fn compare(&self, a: &Key, b: &Key) -> Option<Ordering> {
+        self.0.compare(a, b).map(Ordering::reverse)
+    }
+}
+
+/// compares string keys without regard to case (no allocation)
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct CaseInsensitive;
+
+impl<Key : AsRef<str> + ?Sized> Comparator<Key> for CaseInsensitive {
+    fn compare(&self, a: &Key, b: &Key) -> Option<Ordering> {
+        let a = a.as_ref().chars().flat_map(char::to_lowercase);
+        let b = b.as_ref().chars().flat_map(char::to_lowercase);
+        Some(a.cmp(b))
+    }
+}
+
+/// compares keys by a projection (for example a field or a length)
+#[derive(Debug,Clone,Copy,Default)]
+pub struct ByKey<F>(pub F);
+
+impl<Key : ?Sized, Projected : PartialOrd, F : Fn(&Key) -> Projected> Comparator<Key> for ByKey<F> {
+    fn compare(&self, a: &Key, b: &Key) -> Option<Ordering> {
+        (self.0)(a).partial_cmp(&(self.0)(b))
+    }
+}
+
+/// adapts a comparator to the compare functions taken by the sorted BinTree methods
+pub(crate) fn cmp_fn<Key : ?Sized, C : Comparator<Key>>(comparator: &C) -> impl Fn(&Key, &Key) -> Option<Ordering> + '_ {
+    move |a, b| comparator.compare(a, b)
+}
+
+/// some tests
+#[cfg(test)]
+mod test {
+    use core::cmp::Ordering::*;
+    use crate::{Comparator, NaturalOrder, TotalOrder, FloatTotalOrder, ReverseOrder, CaseInsensitive, ByKey};
+
+    #[test]
+    fn test_comparators() {
+        assert_eq!(NaturalOrder.compare(&1, &2), Some(Less));
+        assert_eq!(NaturalOrder.compare(&f64::NAN, &2.0), None);
+        assert_eq!(TotalOrder.compare("b", "a"), Some(Greater));
+        assert_eq!(FloatTotalOrder.compare(&f64::NAN, &f64::INFINITY), Some(Greater));
+        assert_eq!(FloatTotalOrder.compare(&-0.0f32, &0.0), Some(Less));
+        assert_eq!(ReverseOrder(NaturalOrder).compare(&1, &2), Some(Greater));
+        assert_eq!(ReverseOrder(ReverseOrder(NaturalOrder)).compare(&1, &2), Some(Less));
+        assert_eq!(CaseInsensitive.compare("Content-Type", "content-type"), Some(Equal));
+        assert_eq!(CaseInsensitive.compare(&String::from("ACCEPT"), &String::from("age")), Some(Less));
+        assert_eq!(ByKey(|s: &&str| s.len()).compare(&"abc", &"xy"), Some(Greater));
+        assert_eq!(ReverseOrder(ByKey(|s: &&str| s.len())).compare(&"abc", &"xy"), Some(Less));
+    }
+}
@@ -0,0 +1,231 @@
+use alloc::vec::Vec;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::ops::{Deref, DerefMut};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{BinTree, BinTreeMap, TreePath};
+
+/// default number of shards
+const DEFAULT_SHARDS : usize = 16;
+
+/// a thread-safe map: keys are spread by hash over several BinTreeMap shards, each behind a RwLock
+/// 
+/// consistency model:
+/// - get, insert, remove, contains_key and entry lock a single shard, so they are atomic
+///   (linearizable) for a given key, and operations on keys in different shards don't contend
+/// - an entry (and the value guard returned by or_insert) holds the write lock of its shard until
+///   it is dropped: don't call other methods of the map while holding one (same shard would
+///   deadlock) or across an await point
+/// - len and is_empty read the shards one after the other, so they are not a point-in-time
+///   value when other threads are writing
+/// - snapshot holds the read locks of all the shards at the same time, so it is a consistent
+///   point-in-time copy of the whole map (writers wait while it is taken)
+/// - a panic while a shard is locked poisons the lock and later accesses to the shard panic
+#[derive(Debug)]
+pub struct ConcurrentBinTreeMap<Key,Value,S = RandomState> {
+    shards: Vec<RwLock<BinTreeMap<Key,Value>>>,
+    hasher: S,
+}
+
+impl<Key : PartialOrd + Hash, Value> Default for ConcurrentBinTreeMap<Key,Value> {
+    fn default() -> Self {
+        Self::with_shards(DEFAULT_SHARDS)
+    }
+}
+
+impl<Key : PartialOrd + Hash, Value> ConcurrentBinTreeMap<Key,Value> {
+    /// empty map with the default number of shards
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// empty map with a given number of shards (at least 1)
+    pub fn with_shards(shards : usize) -> Self {
+        Self::with_shards_and_hasher(shards, RandomState::new())
+    }
+}
+
+impl<Key : PartialOrd + Hash, Value, S : BuildHasher> ConcurrentBinTreeMap<Key,Value,S> {
+    /// empty map with a given number of shards (at least 1) and a custom hasher to pick the shards
+    pub fn with_shards_and_hasher(shards : usize, hasher : S) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| RwLock::new(BinTreeMap::new())).collect(),
+            hasher,
+        }
+    }
+    /// number of shards
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+    fn shard_index<Q : Hash + ?Sized>(&self, key : &Q) -> usize {
+        (self.hasher.hash_one(key) % self.shards.len() as u64) as usize
+    }
+    fn read_shard<Q : Hash + ?Sized>(&self, key : &Q) -> RwLockReadGuard<'_, BinTreeMap<Key,Value>> {
+        self.shards[self.shard_index(key)].read().expect("shard lock poisoned")
+    }
+    fn write_shard<Q : Hash + ?Sized>(&self, key : &Q) -> RwLockWriteGuard<'_, BinTreeMap<Key,Value>> {
+        self.shards[self.shard_index(key)].write().expect("shard lock poisoned")
+    }
+    /// insert into the map
+    pub fn insert(&self, key : Key, value : Value) {
+        self.write_shard(&key).insert(key, value);
+    }
+    /// get a copy of a value by key
+    pub fn get<Q>(&self, key : &Q) -> Option<Value> where Key : Borrow<Q>, Q : PartialOrd + Hash + ?Sized, Value : Clone {
+        self.read_shard(key).get(key).cloned()
+    }
+    /// apply a function to a value by key (without copying the value)
+    pub fn get_with<Q,F,R>(&self, key : &Q, f : F) -> Option<R> where Key : Borrow<Q>, Q : PartialOrd + Hash + ?Sized, F : FnOnce(&Value) -> R {
+        self.read_shard(key).get(key).map(f)
+    }
+    /// remove by key from the map and return removed value
+    pub fn remove<Q>(&self, key : &Q) -> Option<Value> where Key : Borrow<Q>, Q : PartialOrd + Hash + ?Sized {
+        self.write_shard(key).remove(key)
+    }
+    /// check if map contains key
+    pub fn contains_key<Q>(&self, key : &Q) -> bool where Key : Borrow<Q>, Q : PartialOrd + Hash + ?Sized {
+        self.read_shard(key).contains_key(key)
+    }
+    /// entry for a key: holds the write lock of the shard until dropped
+    pub fn entry(&self, key : Key) -> ConcurrentBinTreeMapEntry<'_,Key,Value> {
+        ConcurrentBinTreeMapEntry { shard: self.write_shard(&key), key }
+    }
+    /// number of elements in the map (not a point-in-time value under concurrent writes)
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.read().expect("shard lock poisoned").len()).sum()
+    }
+    /// is the map empty ? (not a point-in-time value under concurrent writes)
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| shard.read().expect("shard lock poisoned").is_empty())
+    }
+    /// consistent copy of the whole map (all the shards are read-locked together)
+    pub fn snapshot(&self) -> BinTreeMap<Key,Value> where Key : Clone, Value : Clone {
+        let guards : Vec<_> = self.shards.iter().map(|shard| shard.read().expect("shard lock poisoned")).collect();
+        let mut snapshot = BinTreeMap::new();
+        for (key, value) in guards.iter().flat_map(|shard| shard.iter()) {
+            snapshot.insert(key.clone(), value.clone());
+        }
+        snapshot
+    }
+}
+
+/// entry of a ConcurrentBinTreeMap (holds the write lock of the shard of the key)
+pub struct ConcurrentBinTreeMapEntry<'a,Key,Value> {
+    shard: RwLockWriteGuard<'a, BinTreeMap<Key,Value>>,
+    key: Key,
+}
+
+impl<'a, Key : PartialOrd, Value> ConcurrentBinTreeMapEntry<'a,Key,Value> {
+    /// key of the entry
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+    /// current value of the entry
+    pub fn get(&self) -> Option<&Value> {
+        self.shard.get(&self.key)
+    }
+    /// current value of the entry (mut)
+    pub fn get_mut(&mut self) -> Option<&mut Value> {
+        self.shard.get_mut(&self.key)
+    }
+    /// modify the value if present
+    pub fn and_modify<F>(mut self, f : F) -> Self where F : FnOnce(&mut Value) {
+        if let Some(value) = self.get_mut() {
+            f(value);
+        }
+        self
+    }
+    /// insert a value if absent, the entry becomes a guard of the value
+    pub fn or_insert(self, default : Value) -> ConcurrentBinTreeMapValueMut<'a,Key,Value> {
+        self.or_insert_with(|| default)
+    }
+    /// insert a computed value if absent, the entry becomes a guard of the value
+    pub fn or_insert_with<F>(mut self, f : F) -> ConcurrentBinTreeMapValueMut<'a,Key,Value> where F : FnOnce() -> Value {
+        let path = self.shard.get_or_insert_path_with(self.key, f);
+        ConcurrentBinTreeMapValueMut { shard: self.shard, path }
+    }
+    /// set the value and return the previous one
+    pub fn insert(mut self, value : Value) -> Option<Value> {
+        match self.shard.get_mut(&self.key) {
+            Some(current) => Some(core::mem::replace(current, value)),
+            None => {
+                self.shard.insert(self.key, value);
+                None
+            }
+        }
+    }
+    /// remove the entry and return the value
+    pub fn remove(mut self) -> Option<Value> {
+        self.shard.remove(&self.key)
+    }
+}
+
+/// value of a ConcurrentBinTreeMap entry (holds the write lock of the shard until dropped)
+pub struct ConcurrentBinTreeMapValueMut<'a,Key,Value> {
+    shard: RwLockWriteGuard<'a, BinTreeMap<Key,Value>>,
+    /// path to the entry in the shard (the shard is locked, so it stays valid)
+    path: TreePath,
+}
+
+impl<Key,Value> Deref for ConcurrentBinTreeMapValueMut<'_,Key,Value> {
+    type Target = Value;
+    fn deref(&self) -> &Value {
+        &self.shard.inner().subtree(&self.path).and_then(BinTree::value).expect("path leads to the entry").value
+    }
+}
+
+impl<Key,Value> DerefMut for ConcurrentBinTreeMapValueMut<'_,Key,Value> {
+    fn deref_mut(&mut self) -> &mut Value {
+        self.shard.value_at_mut(&self.path).expect("path leads to the entry")
+    }
+}
+
+/// some tests
+#[cfg(test)]
+mod test {
+    use crate::ConcurrentBinTreeMap;
+
+    #[test]
+    fn test_concurrent_map() {
+        let map = ConcurrentBinTreeMap::with_shards(4);
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                let map = &map;
+                s.spawn(move || {
+                    for i in 0..250 {
+                        map.insert(t * 250 + i, i);
+                        *map.entry(-1).or_insert(0) += 1;
+                    }
+                });
+            }
+        });
+        assert_eq!(map.get(&-1),Some(1000));
+        map.remove(&-1);
+        assert_eq!(map.len(),1000);
+        assert_eq!(map.get(&999),Some(249));
+        assert_eq!(map.get_with(&10, |v| v * 2),Some(20));
+        let snapshot = map.snapshot();
+        assert_eq!(snapshot.keys().copied().collect::<Vec<_>>(),(0..1000).collect::<Vec<_>>());
+        assert_eq!(map.entry(5).and_modify(|v| *v = 0).remove(),Some(0));
+        assert!(!map.contains_key(&5));
+        assert_eq!(map.entry(5).insert(50),None);
+        assert_eq!(map.get(&5),Some(50));
+        assert_eq!(map.entry(5).insert(51),Some(50));
+        assert_eq!(*map.entry(5).or_insert(0),51);
+    }
+
+    #[test]
+    fn test_entry_key_not_clone() {
+        #[derive(Debug, PartialEq, PartialOrd, Hash)]
+        struct Id(u32);
+
+        let map = ConcurrentBinTreeMap::with_shards(2);
+        *map.entry(Id(1)).or_insert_with(|| 10) += 1;
+        *map.entry(Id(1)).or_insert_with(|| unreachable!()) += 1;
+        assert_eq!(map.get(&Id(1)),Some(12));
+        assert_eq!(map.entry(Id(2)).insert(20),None);
+        assert_eq!(map.entry(Id(2)).insert(21),Some(20));
+        assert_eq!(map.len(),2);
+    }
+}
@@ -1,7 +1,16 @@
-use derive_more::Display;
+#![cfg_attr(not(test), no_std)]
+
+// only Box, Vec, VecDeque and String are needed without the std feature
+extern crate alloc;
+#[cfg(all(feature = "std", not(test)))]
+extern crate std;
+
+use alloc::{format, string::String};
 
 /// implementation of a simple binary tree (with optional balancing)
 pub mod bin_tree;
+// the iter and utils_unsafe modules of bin_tree and map share names, use their full paths
+#[allow(ambiguous_glob_reexports)]
 pub use crate::bin_tree::*;
 
 /// a basic ordered set container shows how to encapsulate a type inside another
@@ -12,23 +21,75 @@ pub use crate::ordered_set::*;
 pub mod map;
 pub use crate::map::*;
 
-pub type Result<T> = std::result::Result<T, BinTreeError>;
+/// key orderings carried by the map and the ordered set
+pub mod comparator;
+pub use crate::comparator::*;
+
+/// thread-safe map sharded over several locked BinTreeMaps (std feature)
+#[cfg(feature = "std")]
+pub mod concurrent;
+#[cfg(feature = "std")]
+pub use crate::concurrent::*;
+
+pub type Result<T> = core::result::Result<T, BinTreeError>;
 
-#[derive(Debug,Display,PartialEq)]
+/// errors reported by the fallible (checked, try_*) operations
+#[derive(Debug,Clone,PartialEq,Eq)]
 pub enum BinTreeError {
     SwapSame,
     SwapNotFound1,
     SwapNotFound2,
+    Incomparable,
+    KeyNotFound { key: String },
+    DuplicateKey { key: String },
+    AllocationFailed { bytes: usize },
+}
+
+impl BinTreeError {
+    /// missing key error (the key is recorded in debug format)
+    pub fn key_not_found<Key : core::fmt::Debug + ?Sized>(key : &Key) -> Self {
+        Self::KeyNotFound { key: format!("{:?}", key) }
+    }
+    /// duplicate key error (the key is recorded in debug format)
+    pub fn duplicate_key<Key : core::fmt::Debug + ?Sized>(key : &Key) -> Self {
+        Self::DuplicateKey { key: format!("{:?}", key) }
+    }
+    /// replaces the positional swap errors with the missing key
+    pub(crate) fn with_swap_keys<Key : core::fmt::Debug + ?Sized>(self, key1 : &Key, key2 : &Key) -> Self {
+        match self {
+            Self::SwapNotFound1 => Self::key_not_found(key1),
+            Self::SwapNotFound2 => Self::key_not_found(key2),
+            error => error,
+        }
+    }
+}
+
+impl core::fmt::Display for BinTreeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use BinTreeError::*;
+        match self {
+            SwapSame => write!(f, "cannot swap an item with itself"),
+            SwapNotFound1 => write!(f, "first item to swap not found"),
+            SwapNotFound2 => write!(f, "second item to swap not found"),
+            Incomparable => write!(f, "keys cannot be compared"),
+            KeyNotFound { key } => write!(f, "key not found: {}", key),
+            DuplicateKey { key } => write!(f, "duplicate key: {}", key),
+            AllocationFailed { bytes } => write!(f, "failed to allocate {} bytes", bytes),
+        }
+    }
 }
 
-impl std::error::Error for BinTreeError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+impl core::error::Error for BinTreeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
         use BinTreeError::*;
         match *self {
             SwapNotFound1 => None,
             SwapNotFound2 => None,
             SwapSame => None,
+            Incomparable => None,
+            KeyNotFound { .. } => None,
+            DuplicateKey { .. } => None,
+            AllocationFailed { .. } => None,
         }
     }
 }
-
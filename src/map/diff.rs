@@ -0,0 +1,42 @@
+use crate::{BinTreeMap, Comparator, MergeJoin, MergeJoinItem};
+
+/// item returned by BinTreeMap::diff (changes needed to go from self to other)
+#[derive(Debug,Clone,PartialEq)]
+pub enum MapDiff<K,V> {
+    Added(K,V),
+    Removed(K,V),
+    Changed(K,V,V),
+}
+
+impl<K : Clone, V : Clone> MapDiff<&K,&V> {
+    /// maps a borrowed diff item to an owned one (like Option::cloned)
+    pub fn cloned(self) -> MapDiff<K,V> {
+        match self {
+            MapDiff::Added(k,v) => MapDiff::Added(k.clone(),v.clone()),
+            MapDiff::Removed(k,v) => MapDiff::Removed(k.clone(),v.clone()),
+            MapDiff::Changed(k,old,new) => MapDiff::Changed(k.clone(),old.clone(),new.clone()),
+        }
+    }
+}
+
+impl<Key, Value, C : Comparator<Key>> BinTreeMap<Key,Value,C> {
+    /// lazy iterator over the differences between self and other in key order
+    /// (Added: only in other, Removed: only in self, Changed: different values)
+    pub fn diff<'a>(&'a self, other : &'a Self) -> impl Iterator<Item = MapDiff<&'a Key, &'a Value>> + 'a where Value : PartialEq {
+        MergeJoin::new(self.iter(), other.iter(), |k1 : &&Key, k2 : &&Key| self.cmp.compare(*k1, *k2))
+            .filter_map(|item| match item {
+                MergeJoinItem::Left(k,v) => Some(MapDiff::Removed(k,v)),
+                MergeJoinItem::Right(k,v) => Some(MapDiff::Added(k,v)),
+                MergeJoinItem::Both(k,old,new) => if old != new { Some(MapDiff::Changed(k,old,new)) } else { None },
+            })
+    }
+    /// applies a diff to the map (Added and Changed insert the new value, Removed removes the key)
+    pub fn patch<I>(&mut self, diff : I) where I : IntoIterator<Item = MapDiff<Key,Value>> {
+        for item in diff {
+            match item {
+                MapDiff::Added(k,v) | MapDiff::Changed(k,_,v) => { self.insert(k,v); },
+                MapDiff::Removed(k,_) => { self.remove(&k); },
+            }
+        }
+    }
+}
@@ -1,17 +1,23 @@
+use core::borrow::Borrow;
+
 /// node entry for BinTreeMap
-#[derive(Debug,Clone,Default)]
-pub struct BinTreeMapEntry<Key,Value> where Key : PartialOrd {
+#[derive(Debug,Clone)]
+pub struct BinTreeMapEntry<Key,Value> {
     pub key: Key,
     pub value: Value,
 }
 
-impl<Key : PartialOrd, Value> BinTreeMapEntry<Key,Value> {
+impl<Key, Value> BinTreeMapEntry<Key,Value> {
     /// get the key from a BinTreeMapEntry
     pub fn key(&self) -> &Key {
         &self.key
     }
+    /// get the key from a BinTreeMapEntry in borrowed form (like &str for a String key)
+    pub fn borrowed_key<Q : ?Sized>(&self) -> &Q where Key : Borrow<Q> {
+        self.key.borrow()
+    }
 }
-impl<Key : PartialOrd, Value> PartialEq for BinTreeMapEntry<Key,Value> {
+impl<Key : PartialEq, Value> PartialEq for BinTreeMapEntry<Key,Value> {
     /// equality for BinTreeMapEntry (by key)
     fn eq(&self, other: &Self) -> bool {
         self.key == other.key
@@ -19,7 +25,7 @@ impl<Key : PartialOrd, Value> PartialEq for BinTreeMapEntry<Key,Value> {
 }
 impl<Key : PartialOrd, Value> PartialOrd for BinTreeMapEntry<Key,Value> {
     /// partial order for BinTreeMapEntry (by key)
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         self.key.partial_cmp(&other.key)
     }
 }
@@ -1,42 +1,51 @@
-use crate::{BinTreeMap, BinTreeIter, BinTreeIterMut, BinTreeIntoIter, BinTreeMapEntry};
+use core::iter::FusedIterator;
+use crate::{BinTreeMap, BinTreeIter, BinTreeIterMut, BinTreeIntoIter, BinTreeExactSizeIter, BinTreeMapEntry, bin_tree::ExtractState};
 
-impl<Key : PartialOrd, Value> BinTreeMap<Key,Value> {
+impl<Key, Value, C> BinTreeMap<Key,Value,C> {
     /// iter for BinTreeMap
     pub fn iter(&self) -> BinTreeMapIter<'_, Key, Value> {
-        BinTreeMapIter{iter:self.data.iter()}
+        BinTreeMapIter{iter:self.data.iter().with_len(self.len)}
     }
     /// iter_mut for BinTreeMap
     pub fn iter_mut(&mut self) -> BinTreeMapIterMut<'_, Key, Value> {
-        BinTreeMapIterMut{iter:self.data.iter_mut()}
+        BinTreeMapIterMut{iter:self.data.iter_mut().with_len(self.len)}
     }
     /// keys for BinTreeMap
     pub fn keys(&self) -> BinTreeMapIterKeys<'_, Key, Value> {
-        BinTreeMapIterKeys{iter:self.data.iter()}
+        BinTreeMapIterKeys{iter:self.data.iter().with_len(self.len)}
     }
     /// values for BinTreeMap
     pub fn values(&self) -> BinTreeMapIterValues<'_, Key, Value> {
-        BinTreeMapIterValues{iter:self.data.iter()}
+        BinTreeMapIterValues{iter:self.data.iter().with_len(self.len)}
+    }
+    /// lazy iterator that removes and returns the entries for which the predicate returns true
+    /// the remaining entries are rebuilt into a balanced tree when the iterator is dropped (linear time)
+    pub fn extract_if<F>(&mut self, pred : F) -> BinTreeMapExtractIf<'_, Key, Value, F> where F : FnMut(&Key, &mut Value) -> bool {
+        BinTreeMapExtractIf{state:ExtractState::new(&mut self.data, &mut self.len), pred}
     }
 }
 
-impl<Key: PartialOrd,Value> IntoIterator for BinTreeMap<Key,Value> {
+impl<Key,Value,C> IntoIterator for BinTreeMap<Key,Value,C> {
     type IntoIter = BinTreeMapIntoIter<Key,Value>;
     type Item = (Key, Value);
 
     fn into_iter(self) -> Self::IntoIter {
-        BinTreeMapIntoIter{iter:self.data.into_iter()}
+        BinTreeMapIntoIter{iter:self.data.into_iter().with_len(self.len)}
     }
 }
 
 /// into_iter for BinTreeMap (uses BinTree iterator)
 #[repr(transparent)]
-pub struct BinTreeMapIntoIter<K,V> where K : PartialOrd {
-    iter: BinTreeIntoIter<BinTreeMapEntry<K,V>>
+pub struct BinTreeMapIntoIter<K,V> {
+    iter: BinTreeExactSizeIter<BinTreeIntoIter<BinTreeMapEntry<K,V>>>
 }
 
-impl<K : PartialOrd,V> Iterator for BinTreeMapIntoIter<K,V> {
+impl<K,V> Iterator for BinTreeMapIntoIter<K,V> {
     type Item = (K, V);
 
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(BinTreeMapEntry{key,value}) = self.iter.next() {
             Some((key,value))
@@ -46,7 +55,11 @@ impl<K : PartialOrd,V> Iterator for BinTreeMapIntoIter<K,V> {
     }
 }
 
-impl<'a,Key: PartialOrd,Value> IntoIterator for &'a BinTreeMap<Key,Value> {
+impl<K,V> ExactSizeIterator for BinTreeMapIntoIter<K,V> {}
+
+impl<K,V> FusedIterator for BinTreeMapIntoIter<K,V> {}
+
+impl<'a,Key,Value,C> IntoIterator for &'a BinTreeMap<Key,Value,C> {
     type IntoIter = BinTreeMapIter<'a,Key,Value>;
     type Item = (&'a Key, &'a Value);
 
@@ -57,13 +70,16 @@ impl<'a,Key: PartialOrd,Value> IntoIterator for &'a BinTreeMap<Key,Value> {
 
 /// iter for BinTreeMap (uses BinTree iterator)
 #[repr(transparent)]
-pub struct BinTreeMapIter<'a,K,V> where K : PartialOrd {
-    iter: BinTreeIter<'a,BinTreeMapEntry<K,V>>
+pub struct BinTreeMapIter<'a,K,V> {
+    iter: BinTreeExactSizeIter<BinTreeIter<'a,BinTreeMapEntry<K,V>>>
 }
 
-impl<'a,K : PartialOrd,V> Iterator for BinTreeMapIter<'a,K,V> {
+impl<'a,K,V> Iterator for BinTreeMapIter<'a,K,V> {
     type Item = (&'a K, &'a V);
 
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(BinTreeMapEntry{key,value}) = self.iter.next() {
             Some((key,value))
@@ -73,7 +89,11 @@ impl<'a,K : PartialOrd,V> Iterator for BinTreeMapIter<'a,K,V> {
     }
 }
 
-impl<'a,Key: PartialOrd,Value> IntoIterator for &'a mut BinTreeMap<Key,Value> {
+impl<'a,K,V> ExactSizeIterator for BinTreeMapIter<'a,K,V> {}
+
+impl<'a,K,V> FusedIterator for BinTreeMapIter<'a,K,V> {}
+
+impl<'a,Key,Value,C> IntoIterator for &'a mut BinTreeMap<Key,Value,C> {
     type IntoIter = BinTreeMapIterMut<'a,Key,Value>;
     type Item = (&'a Key, &'a mut Value);
 
@@ -84,13 +104,16 @@ impl<'a,Key: PartialOrd,Value> IntoIterator for &'a mut BinTreeMap<Key,Value> {
 
 /// iter_mut for BinTreeMap (uses BinTree iterator)
 #[repr(transparent)]
-pub struct BinTreeMapIterMut<'a,K,V> where K : PartialOrd {
-    iter: BinTreeIterMut<'a,BinTreeMapEntry<K,V>>
+pub struct BinTreeMapIterMut<'a,K,V> {
+    iter: BinTreeExactSizeIter<BinTreeIterMut<'a,BinTreeMapEntry<K,V>>>
 }
 
-impl<'a,K : PartialOrd,V> Iterator for BinTreeMapIterMut<'a,K,V> {
+impl<'a,K,V> Iterator for BinTreeMapIterMut<'a,K,V> {
     type Item = (&'a K, &'a mut V);
 
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(BinTreeMapEntry{key,value}) = self.iter.next() {
             Some((key,value))
@@ -100,15 +123,22 @@ impl<'a,K : PartialOrd,V> Iterator for BinTreeMapIterMut<'a,K,V> {
     }
 }
 
+impl<'a,K,V> ExactSizeIterator for BinTreeMapIterMut<'a,K,V> {}
+
+impl<'a,K,V> FusedIterator for BinTreeMapIterMut<'a,K,V> {}
+
 /// keys iter for BinTreeMap (uses BinTree iterator)
 #[repr(transparent)]
-pub struct BinTreeMapIterKeys<'a,K,V> where K : PartialOrd {
-    iter: BinTreeIter<'a,BinTreeMapEntry<K,V>>
+pub struct BinTreeMapIterKeys<'a,K,V> {
+    iter: BinTreeExactSizeIter<BinTreeIter<'a,BinTreeMapEntry<K,V>>>
 }
 
-impl<'a,K : PartialOrd,V> Iterator for BinTreeMapIterKeys<'a,K,V> {
+impl<'a,K,V> Iterator for BinTreeMapIterKeys<'a,K,V> {
     type Item = &'a K;
 
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(BinTreeMapEntry{key,value: _}) = self.iter.next() {
             Some(key)
@@ -118,15 +148,22 @@ impl<'a,K : PartialOrd,V> Iterator for BinTreeMapIterKeys<'a,K,V> {
     }
 }
 
+impl<'a,K,V> ExactSizeIterator for BinTreeMapIterKeys<'a,K,V> {}
+
+impl<'a,K,V> FusedIterator for BinTreeMapIterKeys<'a,K,V> {}
+
 /// values iter for BinTreeMap (uses BinTree iterator)
 #[repr(transparent)]
-pub struct BinTreeMapIterValues<'a,K,V> where K : PartialOrd {
-    iter: BinTreeIter<'a,BinTreeMapEntry<K,V>>
+pub struct BinTreeMapIterValues<'a,K,V> {
+    iter: BinTreeExactSizeIter<BinTreeIter<'a,BinTreeMapEntry<K,V>>>
 }
 
-impl<'a,K : PartialOrd,V> Iterator for BinTreeMapIterValues<'a,K,V> {
+impl<'a,K,V> Iterator for BinTreeMapIterValues<'a,K,V> {
     type Item = &'a V;
 
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(BinTreeMapEntry{key: _,value}) = self.iter.next() {
             Some(value)
@@ -135,3 +172,22 @@ impl<'a,K : PartialOrd,V> Iterator for BinTreeMapIterValues<'a,K,V> {
         }
     }
 }
+
+impl<'a,K,V> ExactSizeIterator for BinTreeMapIterValues<'a,K,V> {}
+
+impl<'a,K,V> FusedIterator for BinTreeMapIterValues<'a,K,V> {}
+
+/// extract_if iterator for BinTreeMap (uses BinTree extraction)
+pub struct BinTreeMapExtractIf<'a,K,V,F> where F : FnMut(&K, &mut V) -> bool {
+    state: ExtractState<'a,BinTreeMapEntry<K,V>>,
+    pred: F,
+}
+
+impl<K,V,F> Iterator for BinTreeMapExtractIf<'_,K,V,F> where F : FnMut(&K, &mut V) -> bool {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pred = &mut self.pred;
+        self.state.next_with(|kv| pred(&kv.key, &mut kv.value)).map(|BinTreeMapEntry{key,value}| (key,value))
+    }
+}
@@ -0,0 +1,78 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::iter::Peekable;
+
+use crate::{BinTree, BinTreeMap, BinTreeMapEntry, Comparator};
+
+/// item returned by merge_join: a key found only in the left map, only in the right map, or in both
+#[derive(Debug,Clone,PartialEq)]
+pub enum MergeJoinItem<K,V> {
+    Left(K,V),
+    Right(K,V),
+    Both(K,V,V),
+}
+
+impl<Key, Value, C : Comparator<Key>> BinTreeMap<Key,Value,C> {
+    /// lazy iterator walking both maps in key order (uses the comparator of self)
+    /// incomparable keys are treated as equal
+    pub fn merge_join<'a>(&'a self, other : &'a Self) -> impl Iterator<Item = MergeJoinItem<&'a Key, &'a Value>> + 'a {
+        MergeJoin::new(self.iter(), other.iter(), |k1 : &&Key, k2 : &&Key| self.cmp.compare(*k1, *k2))
+    }
+    /// merge other into self, keys found in both maps are resolved by the callback
+    /// (None drops the key from the result)
+    /// the tree is rebuilt balanced in linear time
+    pub fn merge_with<F>(self, other : Self, mut resolve : F) -> Self where F : FnMut(&Key, Value, Value) -> Option<Value> {
+        let Self { data, cmp, .. } = self;
+        let entries : Vec<_> = MergeJoin::new(data.into_iter().map(|kv| (kv.key, kv.value)),
+                other.into_iter(), |k1 : &Key, k2 : &Key| cmp.compare(k1, k2))
+            .filter_map(|item| match item {
+                MergeJoinItem::Left(key,value) | MergeJoinItem::Right(key,value) => Some(BinTreeMapEntry{key,value}),
+                MergeJoinItem::Both(key,left,right) => resolve(&key,left,right).map(|value| BinTreeMapEntry{key,value}),
+            })
+            .collect();
+        Self {
+            len: entries.len(),
+            data: BinTree::from_sorted(entries),
+            cmp,
+        }
+    }
+}
+
+/// merges two iterators of (key,value) sorted by the same compare function
+pub(crate) struct MergeJoin<L : Iterator, R : Iterator, F> {
+    left: Peekable<L>,
+    right: Peekable<R>,
+    cmp: F,
+}
+
+impl<L : Iterator, R : Iterator, F> MergeJoin<L,R,F> {
+    pub(crate) fn new(left : L, right : R, cmp : F) -> Self {
+        Self { left: left.peekable(), right: right.peekable(), cmp }
+    }
+}
+
+impl<K,V,L,R,F> Iterator for MergeJoin<L,R,F> where 
+    L : Iterator<Item = (K,V)>,
+    R : Iterator<Item = (K,V)>,
+    F : Fn(&K, &K) -> Option<Ordering>,
+{
+    type Item = MergeJoinItem<K,V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let order = match (self.left.peek(), self.right.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((k1,_)), Some((k2,_))) => (self.cmp)(k1,k2).unwrap_or(Ordering::Equal),
+        };
+        match order {
+            Ordering::Less => self.left.next().map(|(k,v)| MergeJoinItem::Left(k,v)),
+            Ordering::Greater => self.right.next().map(|(k,v)| MergeJoinItem::Right(k,v)),
+            Ordering::Equal => {
+                let (k,v1) = self.left.next().expect("left was peeked");
+                let (_,v2) = self.right.next().expect("right was peeked");
+                Some(MergeJoinItem::Both(k,v1,v2))
+            },
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use alloc::{vec, vec::Vec};
+use core::hash::Hash;
+use core::ops::Bound;
+
+use crate::{BinTree, BinTreeMap, BinTreeMapEntry, Comparator};
+
+/// a range of keys (bounds borrowed from the map)
+pub type KeyRange<'a, Key> = (Bound<&'a Key>, Bound<&'a Key>);
+
+impl<Key : Hash, Value : Hash, C> BinTreeMap<Key,Value,C> {
+    /// merkle hash of the map (see BinTree::root_hash)
+    /// maps with the same entries in a different tree shape have different hashes
+    pub fn root_hash(&self) -> u64 {
+        self.data.merkle_hash(&hash_entry)
+    }
+    /// compares the merkle hashes of two maps (O(1) when the hashes are cached)
+    pub fn merkle_eq(&self, other : &Self) -> bool {
+        self.root_hash() == other.root_hash()
+    }
+}
+
+impl<Key : Hash, Value : Hash, C : Comparator<Key>> BinTreeMap<Key,Value,C> {
+    /// anti-entropy: key ranges (disjoint, in key order) outside of which both maps have the same entries
+    /// subtrees with equal hashes are skipped, so the search is cheap when the maps share their shape
+    /// (for example replicas built with the same operations): where the shapes diverge, the whole
+    /// range of the subtree is reported
+    pub fn diff_ranges<'a>(&'a self, other : &'a Self) -> Vec<KeyRange<'a, Key>> {
+        let mut ranges = vec![];
+        self.diff_ranges_rec(&self.data, &other.data, Bound::Unbounded, Bound::Unbounded, &mut ranges);
+        ranges
+    }
+    fn diff_ranges_rec<'a>(&self, t1 : &'a BinTree<BinTreeMapEntry<Key,Value>>, t2 : &'a BinTree<BinTreeMapEntry<Key,Value>>, 
+        low : Bound<&'a Key>, high : Bound<&'a Key>, ranges : &mut Vec<KeyRange<'a, Key>>) 
+    {
+        if t1.merkle_hash(&hash_entry) == t2.merkle_hash(&hash_entry) {
+            return;
+        }
+        match (t1.node(), t2.node()) {
+            (Some((kv1,left1,right1)), Some((kv2,left2,right2))) 
+                if self.cmp.compare(&kv1.key, &kv2.key) == Some(core::cmp::Ordering::Equal) => 
+            {
+                self.diff_ranges_rec(left1, left2, low, Bound::Excluded(&kv1.key), ranges);
+                if hash_value(kv1) != hash_value(kv2) {
+                    ranges.push((Bound::Included(&kv1.key), Bound::Included(&kv1.key)));
+                }
+                self.diff_ranges_rec(right1, right2, Bound::Excluded(&kv1.key), high, ranges);
+            },
+            _ => ranges.push((low, high)),
+        }
+    }
+}
+
+fn hash_entry<Key : Hash, Value : Hash>(kv : &BinTreeMapEntry<Key,Value>, state : &mut std::collections::hash_map::DefaultHasher) {
+    kv.key.hash(state);
+    kv.value.hash(state);
+}
+
+fn hash_value<Key : Hash, Value : Hash>(kv : &BinTreeMapEntry<Key,Value>) -> u64 {
+    use core::hash::Hasher;
+    let mut state = std::collections::hash_map::DefaultHasher::new();
+    hash_entry(kv, &mut state);
+    state.finish()
+}
+
+/// some tests
+#[cfg(test)]
+mod test {
+    use core::ops::Bound::*;
+    use crate::BinTreeMap;
+
+    #[test]
+    fn test_map_diff_ranges() {
+        let mut replica1 = BinTreeMap::new();
+        for k in 0..100 { replica1.insert(k, k * 10); }
+        let mut replica2 = replica1.clone();
+        assert!(replica1.merkle_eq(&replica2));
+        assert_eq!(replica1.diff_ranges(&replica2),vec![]);
+        *replica2.get_mut(&42).unwrap() = 0;
+        *replica2.get_mut(&7).unwrap() = 0;
+        assert!(!replica1.merkle_eq(&replica2));
+        assert_eq!(replica1.diff_ranges(&replica2),vec![(Included(&7),Included(&7)),(Included(&42),Included(&42))]);
+        replica2.insert(42, 420);
+        replica2.insert(7, 70);
+        assert_eq!(replica1.root_hash(),replica2.root_hash());
+        // a missing key changes the shape below its parent
+        replica2.remove(&99);
+        let ranges = replica1.diff_ranges(&replica2);
+        assert_eq!(ranges.len(),1);
+        assert!(core::ops::RangeBounds::contains(&ranges[0],&&99));
+        assert!(!core::ops::RangeBounds::contains(&ranges[0],&&50));
+    }
+}
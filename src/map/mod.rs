@@ -1,8 +1,10 @@
-use crate::{BinTree};
+use alloc::{format, string::String, vec::Vec};
+use core::borrow::Borrow;
+
+use crate::{BinTree, BinTreeError, Comparator, NaturalOrder, Result, TreePath, comparator::cmp_fn};
 
 /// methods that use bits of unsafe code
 pub mod utils_unsafe;
-pub use self::utils_unsafe::*;
 
 /// node entry for BinTreeMap
 pub mod entry;
@@ -12,28 +14,61 @@ pub use self::entry::*;
 pub mod iter;
 pub use self::iter::*;
 
+/// merging of two maps (merge_join, merge_with)
+mod merge;
+pub use self::merge::*;
+pub(crate) use self::merge::MergeJoin;
+
+/// parallel iterators for BinTreeMap (rayon feature)
+#[cfg(feature = "rayon")]
+mod par_iter;
+
+/// merkle hashing and anti-entropy (merkle feature)
+#[cfg(feature = "merkle")]
+mod merkle;
+#[cfg(feature = "merkle")]
+pub use self::merkle::*;
+
+/// differences between two maps (diff, patch)
+mod diff;
+pub use self::diff::*;
+
 /// a basic map implementation using BinTree
+/// keys are ordered by the comparator (natural order by default)
 #[derive(Debug,Clone)]
-pub struct BinTreeMap<Key,Value> where Key : PartialOrd {
+pub struct BinTreeMap<Key,Value,C = NaturalOrder> {
     data: BinTree<BinTreeMapEntry<Key,Value>>,
     len: usize,
+    cmp: C,
 }
 
-/// default set is an empty tree
-impl<Key : PartialOrd, Value> Default for BinTreeMap<Key,Value> {
+/// default map is an empty tree
+impl<Key, Value, C : Default> Default for BinTreeMap<Key,Value,C> {
     fn default() -> Self {
-        Self {
-            data: BinTree::default(),
-            len: 0,
-        }
+        Self::with_comparator(C::default())
     }
 }
 
-impl<Key : PartialOrd, Value> BinTreeMap<Key,Value> {
+impl<Key, Value> BinTreeMap<Key,Value> {
     /// empty tree
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<Key, Value, C> BinTreeMap<Key,Value,C> {
+    /// empty tree ordered by a custom comparator
+    pub fn with_comparator(cmp : C) -> Self {
+        Self {
+            data: BinTree::default(),
+            len: 0,
+            cmp,
+        }
+    }
+    /// returns a ref to the comparator used to order the keys
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
     /// number of elements in the map
     pub fn len(&self) -> usize {
         self.len
@@ -42,14 +77,39 @@ impl<Key : PartialOrd, Value> BinTreeMap<Key,Value> {
     pub fn is_empty(&self) -> bool {
         self.data.is_empty()
     }
+    /// transforms the map into the wrapped binary tree
+    pub fn into_inner(self) -> BinTree<BinTreeMapEntry<Key,Value>> {
+        self.data
+    }
+    /// returns a ref to the wrapped binary tree
+    pub fn inner(&self) -> &BinTree<BinTreeMapEntry<Key,Value>> {
+        &self.data
+    }
+    /// mutable value of the entry at the end of a path in the inner tree
+    pub(crate) fn value_at_mut(&mut self, path: &TreePath) -> Option<&mut Value> {
+        self.data.value_at_mut(path).map(|entry| &mut entry.value)
+    }
+    pub fn to_tree_string(&self) -> String where Key: core::fmt::Debug, Value : core::fmt::Debug {
+        format!("{}",self.inner())
+    }
+    /// keep only the entries for which the predicate returns true
+    /// the tree is rebuilt balanced in linear time (uses extract_if)
+    pub fn retain<F>(&mut self, mut pred : F) where F : FnMut(&Key, &mut Value) -> bool {
+        self.extract_if(|k,v| !pred(k,v)).for_each(drop);
+    }
+}
+
+impl<Key, Value, C : Comparator<Key>> BinTreeMap<Key,Value,C> {
     /// insert into the map
+    /// a key that can't be compared (NaN for example) replaces an arbitrary entry (see insert_checked)
     pub fn insert(&mut self, key: Key, value: Value) {
-        if self.data.insert_unique(BinTreeMapEntry{key,value}) {
+        if self.data.insert_to_key_cmp(BinTreeMapEntry{key,value},
+                BinTreeMapEntry::key, cmp_fn(&self.cmp), true, true).is_none() {
             self.len += 1;
         }
     }
     /// get a value by key from the map
-    pub fn get(&self, key: &Key) -> Option<&Value> {
+    pub fn get<Q>(&self, key: &Q) -> Option<&Value> where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
         if let Some((_, value)) = self.get_key_value(key) {
             Some(value)
         } else {
@@ -57,57 +117,214 @@ impl<Key : PartialOrd, Value> BinTreeMap<Key,Value> {
         }
     }
     /// get (key,value) by key from the map
-    pub fn get_key_value(&self, target_key: &Key) -> Option<(&Key, &Value)> {
+    pub fn get_key_value<Q>(&self, target_key: &Q) -> Option<(&Key, &Value)> where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
         if let Some(kv) = self.data.get_sorted_to_key_cmp(target_key, 
-                BinTreeMapEntry::key, Key::partial_cmp) {
+                BinTreeMapEntry::borrowed_key, cmp_fn(&self.cmp)) {
             Some((&kv.key,&kv.value))
         } else {
             None
         }
     }
     /// get mut value by key from the map
-    pub fn get_mut(&mut self, key: &Key) -> Option<&mut Value> {
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut Value> where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
         if let Some(kv) = self.data.get_mut_sorted_to_key_cmp(key, 
-                BinTreeMapEntry::key, Key::partial_cmp) {
+                BinTreeMapEntry::borrowed_key, cmp_fn(&self.cmp)) {
             Some(&mut kv.value)
         } else {
             None
         }
     }
     /// remove by key from the map and return removed value
-    pub fn remove(&mut self, target_key: &Key) -> Option<Value> {
+    pub fn remove<Q>(&mut self, target_key: &Q) -> Option<Value> where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
         // if let Some(kv) = self.data.remove_sorted_with_key(key, &BinTreeMapEntry::key) {
         if let Some(kv) = self.data.remove_sorted_to_key_cmp(target_key, 
-                BinTreeMapEntry::key, Key::partial_cmp, true) {
+                BinTreeMapEntry::borrowed_key, cmp_fn(&self.cmp), true) {
             self.len -= 1;
             Some(kv.value)
         } else {
             None
         }
     }
+    /// remove the entry with the smallest key and return it
+    pub fn pop_first(&mut self) -> Option<(Key, Value)> {
+        if let Some(kv) = self.data.pop_min_sorted(true) {
+            self.len -= 1;
+            Some((kv.key, kv.value))
+        } else {
+            None
+        }
+    }
+    /// remove the entry with the largest key and return it
+    pub fn pop_last(&mut self) -> Option<(Key, Value)> {
+        if let Some(kv) = self.data.pop_max_sorted(true) {
+            self.len -= 1;
+            Some((kv.key, kv.value))
+        } else {
+            None
+        }
+    }
     /// check if map contains key
-    pub fn contains_key(&self, key: &Key) -> bool {
+    pub fn contains_key<Q>(&self, key: &Q) -> bool where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
         self.get(key).is_some()
     }
-    /// transforms the map into the wrapped binary tree
-    pub fn into_inner(self) -> BinTree<BinTreeMapEntry<Key,Value>> {
-        self.data
+    /// insert into the map
+    /// fails with BinTreeError::Incomparable if the key can't be compared (the map is left unchanged)
+    pub fn insert_checked(&mut self, key: Key, value: Value) -> Result<()> {
+        if self.data.insert_to_key_cmp_checked(BinTreeMapEntry{key,value},
+                BinTreeMapEntry::key, cmp_fn(&self.cmp), true, true)?.is_none() {
+            self.len += 1;
+        }
+        Ok(())
     }
-    /// returns a ref to the wrapped binary tree
-    pub fn inner(&self) -> &BinTree<BinTreeMapEntry<Key,Value>> {
-        &self.data
+    /// get a value by key from the map
+    /// fails with BinTreeError::Incomparable if the key can't be compared
+    pub fn get_checked<Q>(&self, key: &Q) -> Result<Option<&Value>> where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        Ok(self.data.get_sorted_to_key_cmp_checked(key, 
+                BinTreeMapEntry::borrowed_key, cmp_fn(&self.cmp))?.map(|kv| &kv.value))
     }
-    pub fn to_tree_string(&self) -> String where Key: std::fmt::Debug, Value : std::fmt::Debug {
-        format!("{}",self.inner())
+    /// get mut value by key from the map
+    /// fails with BinTreeError::Incomparable if the key can't be compared
+    pub fn get_mut_checked<Q>(&mut self, key: &Q) -> Result<Option<&mut Value>> where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        Ok(self.data.get_mut_sorted_to_key_cmp_checked(key, 
+                BinTreeMapEntry::borrowed_key, cmp_fn(&self.cmp))?.map(|kv| &mut kv.value))
+    }
+    /// remove by key from the map and return removed value
+    /// fails with BinTreeError::Incomparable if the key can't be compared (the map is left unchanged)
+    pub fn remove_checked<Q>(&mut self, key: &Q) -> Result<Option<Value>> where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        let removed = self.data.remove_sorted_to_key_cmp_checked(key, 
+                BinTreeMapEntry::borrowed_key, cmp_fn(&self.cmp), true)?;
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        Ok(removed.map(|kv| kv.value))
+    }
+    /// check if map contains key
+    /// fails with BinTreeError::Incomparable if the key can't be compared
+    pub fn contains_key_checked<Q>(&self, key: &Q) -> Result<bool> where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        Ok(self.get_checked(key)?.is_some())
+    }
+    /// insert a new key into the map
+    /// fails with BinTreeError::DuplicateKey if the key is already present (the map is left unchanged)
+    pub fn try_insert(&mut self, key: Key, value: Value) -> Result<()> where Key : core::fmt::Debug {
+        if self.contains_key_checked(&key)? {
+            return Err(BinTreeError::duplicate_key(&key));
+        }
+        self.insert_checked(key, value)
+    }
+    /// remove by key from the map and return removed value
+    /// fails with BinTreeError::KeyNotFound if the key is not in the map
+    pub fn try_remove<Q>(&mut self, key: &Q) -> Result<Value> where Key : Borrow<Q>, Q : core::fmt::Debug + ?Sized, C : Comparator<Q> {
+        self.remove_checked(key)?.ok_or_else(|| BinTreeError::key_not_found(key))
+    }
+    /// mutable ref to the value of a key, inserted with f if the key is not in the map
+    /// one descent (uses get_or_insert_path_to_key_cmp tree method)
+    pub fn get_or_insert_with<F>(&mut self, key: Key, f: F) -> &mut Value where F : FnOnce() -> Value {
+        let path = self.get_or_insert_path_with(key, f);
+        self.value_at_mut(&path).expect("path leads to the entry")
+    }
+    /// path to the entry of a key in the inner tree, inserted with f if the key is not in the map
+    /// (valid until the map is changed)
+    pub(crate) fn get_or_insert_path_with<F>(&mut self, key: Key, f: F) -> TreePath where F : FnOnce() -> Value {
+        let mut inserted = false;
+        let path = self.data.get_or_insert_path_to_key_cmp(key, |key| {
+            inserted = true;
+            BinTreeMapEntry{key, value: f()}
+        }, BinTreeMapEntry::key, cmp_fn(&self.cmp), true);
+        if inserted {
+            self.len += 1;
+        }
+        path
+    }
+    /// mutable ref to the value of a key, inserted as default if the key is not in the map
+    pub fn get_or_insert_default(&mut self, key: Key) -> &mut Value where Value : Default {
+        self.get_or_insert_with(key, Value::default)
+    }
+}
+
+impl<Key, Value, C, Q> core::ops::Index<&Q> for BinTreeMap<Key,Value,C> where
+    Key : Borrow<Q>, Q : ?Sized, C : Comparator<Key> + Comparator<Q>
+{
+    type Output = Value;
+    /// value of a key, panics if the key is not in the map
+    fn index(&self, key: &Q) -> &Value {
+        self.get(key).expect("key not found in BinTreeMap")
+    }
+}
+
+impl<Key, Value, C : Comparator<Key>> Extend<(Key, Value)> for BinTreeMap<Key,Value,C> {
+    /// extend a map from an iterator (existing keys are replaced)
+    fn extend<T: IntoIterator<Item = (Key, Value)>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|(key, value)| self.insert(key, value));
     }
 }
 
-impl<Key: PartialOrd + std::fmt::Debug, Value: std::fmt::Debug> std::fmt::Display for BinTreeMap<Key,Value> {
+impl<'a, Key : Copy, Value : Copy, C : Comparator<Key>> Extend<(&'a Key, &'a Value)> for BinTreeMap<Key,Value,C> {
+    /// extend a map from an iterator of refs (existing keys are replaced)
+    fn extend<T: IntoIterator<Item = (&'a Key, &'a Value)>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|(key, value)| self.insert(*key, *value));
+    }
+}
+
+impl<Key, Value, C : Comparator<Key> + Default> FromIterator<(Key, Value)> for BinTreeMap<Key,Value,C> {
+    /// create a map from an iterator (the last value of a key is kept)
+    fn from_iter<T: IntoIterator<Item = (Key, Value)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<Key : PartialOrd, Value, const N: usize> From<[(Key, Value); N]> for BinTreeMap<Key,Value> {
+    /// create a map from an array (natural order only, so that the comparator can be inferred)
+    fn from(entries: [(Key, Value); N]) -> Self {
+        Self::from_iter(entries)
+    }
+}
+
+impl<Key, Value, C : Comparator<Key> + Default> From<Vec<(Key, Value)>> for BinTreeMap<Key,Value,C> {
+    /// create a map from a vector
+    fn from(entries: Vec<(Key, Value)>) -> Self {
+        Self::from_iter(entries)
+    }
+}
+
+impl<Key : core::fmt::Debug, Value: core::fmt::Debug, C> core::fmt::Display for BinTreeMap<Key,Value,C> {
     /// display a map as a vector of tuples
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f,"{:?}",self.data.iter().map(|kv| (&kv.key, &kv.value)).collect::<Vec<_>>())
     }
 }
 
+impl<Key : PartialEq, Value : PartialEq, C> PartialEq for BinTreeMap<Key,Value,C> {
+    /// map equality (same sorted entries)
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<Key : Eq, Value : Eq, C> Eq for BinTreeMap<Key,Value,C> {}
+
+impl<Key : PartialOrd, Value : PartialOrd, C> PartialOrd for BinTreeMap<Key,Value,C> {
+    /// lexicographic order of the sorted entries
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<Key : Ord, Value : Ord, C> Ord for BinTreeMap<Key,Value,C> {
+    /// lexicographic order of the sorted entries
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<Key : core::hash::Hash, Value : core::hash::Hash, C> core::hash::Hash for BinTreeMap<Key,Value,C> {
+    /// hash of the length and the sorted entries (consistent with eq)
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        self.iter().for_each(|entry| entry.hash(state));
+    }
+}
+
 #[cfg(test)]
 mod test;
\ No newline at end of file
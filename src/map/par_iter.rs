@@ -0,0 +1,50 @@
+use rayon::iter::ParallelIterator;
+
+use crate::{BinTreeMap, BinTreeMapEntry};
+
+impl<Key, Value, C> BinTreeMap<Key,Value,C> {
+    /// parallel iterator (rayon) for BinTreeMap
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (&Key, &Value)> where Key : Sync, Value : Sync {
+        self.data.par_iter().map(|kv| (&kv.key, &kv.value))
+    }
+    /// parallel mutable iterator (rayon) for BinTreeMap
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (&Key, &mut Value)> where Key : Send + Sync, Value : Send {
+        self.data.par_iter_mut().map(|BinTreeMapEntry{key,value}| (&*key, value))
+    }
+    /// parallel consuming iterator (rayon) for BinTreeMap
+    pub fn into_par_iter(self) -> impl ParallelIterator<Item = (Key, Value)> where Key : Send, Value : Send {
+        self.data.into_par_iter().map(|BinTreeMapEntry{key,value}| (key, value))
+    }
+    /// new map with the values transformed in parallel (rayon), keeps the shape of the tree
+    pub fn par_map_values<F, Mapped>(&self, f : F) -> BinTreeMap<Key,Mapped,C> where 
+        Key : Clone + Send + Sync, Value : Sync, Mapped : Send, C : Clone,
+        F : Fn(&Value) -> Mapped + Sync,
+    {
+        BinTreeMap {
+            data: self.data.par_map(&|kv| BinTreeMapEntry{key: kv.key.clone(), value: f(&kv.value)}),
+            len: self.len,
+            cmp: self.cmp.clone(),
+        }
+    }
+}
+
+/// some tests
+#[cfg(test)]
+mod test {
+    use rayon::iter::ParallelIterator;
+    use crate::BinTreeMap;
+
+    #[test]
+    fn test_map_par_iter() {
+        let mut map = BinTreeMap::new();
+        for k in 0..5_000 { map.insert(k, k as u64); }
+        assert_eq!(map.par_iter().map(|(_,v)| v).sum::<u64>(),(0..5_000).sum());
+        map.par_iter_mut().for_each(|(k,v)| *v += *k as u64);
+        assert_eq!(map.get(&10),Some(&20));
+        let strings = map.par_map_values(|v| v.to_string());
+        assert_eq!(strings.len(),5_000);
+        assert_eq!(strings.get(&4_999).map(String::as_str),Some("9998"));
+        let pairs : Vec<_> = map.into_par_iter().collect();
+        assert_eq!(pairs[..3],[(0,0),(1,2),(2,4)]);
+    }
+}
@@ -20,8 +20,8 @@ fn test_map() {
     assert_eq!(map.to_string(),"[(31, \"value for 31 is less than 50\"), (67, \"second value for 67\")]");
     assert_eq!(*map.get(&31).unwrap(),"value for 31 is less than 50");
     assert_eq!(map.get_key_value(&67).unwrap(),(&67,&String::from("second value for 67")));
-    assert_eq!(map.contains_key(&67),true);
-    assert_eq!(map.contains_key(&167),false);
+    assert!(map.contains_key(&67));
+    assert!(!map.contains_key(&167));
 
     assert_eq!(map.to_tree_string(),
         "((\
@@ -103,3 +103,272 @@ fn check_custom_kv_type() {
         (KeyType(130), ValueType(-2)), 
         (KeyType(3330), ValueType(-1782))]);
 }
+
+#[test]
+fn test_map_comparator() {
+    use crate::{CaseInsensitive, ReverseOrder, NaturalOrder};
+
+    let mut headers : BinTreeMap<String, &str, CaseInsensitive> = BinTreeMap::default();
+    headers.insert(String::from("Content-Type"), "text/plain");
+    headers.insert(String::from("accept"), "*/*");
+    headers.insert(String::from("content-type"), "text/html");
+    assert_eq!(headers.len(),2);
+    assert_eq!(headers.get(&String::from("CONTENT-TYPE")),Some(&"text/html"));
+    assert!(headers.contains_key(&String::from("Accept")));
+    assert_eq!(headers.remove(&String::from("ACCEPT")),Some("*/*"));
+    assert_eq!(headers.to_string(),"[(\"content-type\", \"text/html\")]");
+
+    let mut reversed = BinTreeMap::with_comparator(ReverseOrder(NaturalOrder));
+    for (k,v) in [(2,'b'),(1,'a'),(3,'c')] {
+        reversed.insert(k, v);
+    }
+    assert_eq!(reversed.to_string(),"[(3, 'c'), (2, 'b'), (1, 'a')]");
+    assert_eq!(reversed.get(&1),Some(&'a'));
+    assert_eq!(reversed.pop_first(),Some((3,'c')));
+}
+
+#[test]
+fn test_map_incomparable() {
+    use crate::{BinTreeError, FloatTotalOrder};
+
+    let mut metrics = BinTreeMap::new();
+    metrics.insert(0.5, "p50");
+    metrics.insert(0.99, "p99");
+    assert_eq!(metrics.insert_checked(f64::NAN, "bad"),Err(BinTreeError::Incomparable));
+    assert_eq!(metrics.get_checked(&f64::NAN),Err(BinTreeError::Incomparable));
+    assert_eq!(metrics.remove_checked(&f64::NAN),Err(BinTreeError::Incomparable));
+    assert_eq!(metrics.contains_key_checked(&f64::NAN),Err(BinTreeError::Incomparable));
+    assert_eq!(metrics.len(),2);
+    assert_eq!(metrics.to_string(),"[(0.5, \"p50\"), (0.99, \"p99\")]");
+    assert_eq!(metrics.insert_checked(0.9, "p90"),Ok(()));
+    *metrics.get_mut_checked(&0.9).unwrap().unwrap() = "P90";
+    assert_eq!(metrics.get_checked(&0.9),Ok(Some(&"P90")));
+    assert_eq!(metrics.remove_checked(&0.5),Ok(Some("p50")));
+    assert_eq!(metrics.len(),2);
+
+    // with a total order, NaN is a regular key (after infinity)
+    let mut metrics = BinTreeMap::with_comparator(FloatTotalOrder);
+    metrics.insert(0.5, "p50");
+    metrics.insert(f64::NAN, "nan");
+    metrics.insert(f64::INFINITY, "inf");
+    metrics.insert(0.99, "p99");
+    assert_eq!(metrics.len(),4);
+    assert_eq!(metrics.get(&f64::NAN),Some(&"nan"));
+    assert_eq!(metrics.values().copied().collect::<Vec<_>>(),vec!["p50","p99","inf","nan"]);
+}
+
+#[test]
+fn test_map_borrowed_lookup() {
+    use crate::CaseInsensitive;
+
+    let mut map : BinTreeMap<String, usize> = BinTreeMap::new();
+    map.insert(String::from("one"), 1);
+    map.insert(String::from("two"), 2);
+    map.insert(String::from("three"), 3);
+    assert_eq!(map.get("two"),Some(&2));
+    assert_eq!(map.get_key_value("one"),Some((&String::from("one"),&1)));
+    assert!(!map.contains_key("four"));
+    *map.get_mut("three").unwrap() += 30;
+    assert_eq!(map.swap("one", "three"),Ok(()));
+    assert_eq!(map.remove("one"),Some(33));
+    assert_eq!(map.get_checked("three"),Ok(Some(&1)));
+    assert_eq!(map.len(),2);
+
+    let mut headers : BinTreeMap<String, &str, CaseInsensitive> = BinTreeMap::default();
+    headers.insert(String::from("Content-Length"), "42");
+    assert_eq!(headers.get("content-length"),Some(&"42"));
+}
+
+#[test]
+fn test_map_try_ops() {
+    use crate::BinTreeError;
+
+    let mut map = BinTreeMap::new();
+    assert_eq!(map.try_insert("one", 1),Ok(()));
+    assert_eq!(map.try_insert("two", 2),Ok(()));
+    assert_eq!(map.try_insert("one", 10),Err(BinTreeError::duplicate_key(&"one")));
+    assert_eq!(map.get("one"),Some(&1));
+    assert_eq!(map.try_swap(&"one", &"three"),Err(BinTreeError::key_not_found(&"three")));
+    assert_eq!(map.try_swap(&"one", &"two"),Ok(()));
+    assert_eq!(map.try_remove(&"one"),Ok(2));
+    assert_eq!(map.try_remove(&"one").unwrap_err().to_string(),"key not found: \"one\"");
+    assert_eq!(map.len(),1);
+    let mut metrics = BinTreeMap::new();
+    assert_eq!(metrics.try_insert(f64::NAN, "nan"),Err(BinTreeError::Incomparable));
+}
+
+#[test]
+fn test_map_retain_extract_if() {
+    let mut sessions : BinTreeMap<u32, u64> = BinTreeMap::new();
+    for id in 0..20 {
+        sessions.insert(id, 100 + id as u64 * 10);
+    }
+    let expired : Vec<_> = sessions.extract_if(|_, expiry| *expiry < 150).collect();
+    assert_eq!(expired.iter().map(|(k,_)| *k).collect::<Vec<_>>(),vec![0,1,2,3,4]);
+    assert_eq!(sessions.len(),15);
+    assert!(sessions.inner().is_balanced());
+    sessions.retain(|id, expiry| { *expiry += 1; id % 2 == 0 });
+    assert_eq!(sessions.len(),7);
+    assert_eq!(sessions.get(&6),Some(&161));
+    assert_eq!(sessions.get(&7),None);
+    assert!(sessions.inner().is_balanced());
+    sessions.insert(7, 0);
+    assert_eq!(sessions.keys().copied().collect::<Vec<_>>(),vec![6,7,8,10,12,14,16,18]);
+    // a leaked iterator leaves an empty map with a consistent length
+    core::mem::forget(sessions.extract_if(|id, _| *id > 10));
+    assert_eq!(sessions.len(),0);
+    assert_eq!(sessions.iter().len(),0);
+    // the item under test is kept when the predicate panics
+    let mut map : BinTreeMap<u32, u32> = (0..10).map(|k| (k, k)).collect();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| map.retain(|k, _| if *k == 5 { panic!() } else { k % 2 == 0 })));
+    assert!(result.is_err());
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(),vec![0,2,4,5,6,7,8,9]);
+    assert_eq!(map.len(),8);
+}
+
+#[test]
+fn test_map_merge() {
+    use crate::MergeJoinItem::*;
+
+    let layer = |entries : &[(&'static str, i32)]| {
+        let mut map = BinTreeMap::new();
+        for &(k,v) in entries { map.insert(k,v); }
+        map
+    };
+    let defaults = layer(&[("color",0),("depth",8),("size",10)]);
+    let site = layer(&[("depth",16),("proxy",1)]);
+    let user = layer(&[("color",2),("proxy",0)]);
+    assert_eq!(defaults.merge_join(&site).collect::<Vec<_>>(),vec![
+        Left(&"color",&0),Both(&"depth",&8,&16),Right(&"proxy",&1),Left(&"size",&10)]);
+    let merged = defaults
+        .merge_with(site, |_,_,site| Some(site))
+        .merge_with(user, |_,_,user| if user == 0 { None } else { Some(user) });
+    assert_eq!(merged.to_string(),"[(\"color\", 2), (\"depth\", 16), (\"size\", 10)]");
+    assert_eq!(merged.len(),3);
+    assert!(merged.inner().is_balanced());
+    assert_eq!(merged.merge_join(&BinTreeMap::new()).count(),3);
+}
+
+#[test]
+fn test_map_diff_patch() {
+    use crate::MapDiff::*;
+
+    let mut replica = BinTreeMap::new();
+    replica.insert(1, "a");
+    replica.insert(2, "b");
+    replica.insert(3, "c");
+    let mut primary = replica.clone();
+    primary.remove(&1);
+    primary.insert(3, "C");
+    primary.insert(4, "d");
+    assert_eq!(replica.diff(&primary).collect::<Vec<_>>(),vec![Removed(&1,&"a"),Changed(&3,&"c",&"C"),Added(&4,&"d")]);
+    let delta : Vec<_> = replica.diff(&primary).map(|d| d.cloned()).collect();
+    replica.patch(delta);
+    assert_eq!(replica.diff(&primary).count(),0);
+    assert_eq!(replica.to_string(),primary.to_string());
+    assert_eq!(replica.len(),3);
+}
+
+#[test]
+#[allow(clippy::mutable_key_type)] // the merkle hash cache is not part of eq/hash
+fn test_map_eq_hash_ord() {
+    use std::collections::HashSet;
+
+    let m1 : BinTreeMap<i32,&str> = (0..10).rev().fold(BinTreeMap::new(), |mut m, i| { m.insert(i, "x"); m });
+    let mut m2 = BinTreeMap::new();
+    (0..10).for_each(|i| { m2.insert(i, "x"); });
+    // same content, different shapes
+    assert!(!m1.inner().shape_eq(m2.inner()));
+    assert_eq!(m1,m2);
+    assert_eq!(m1.cmp(&m2),core::cmp::Ordering::Equal);
+    m2.insert(9, "y");
+    assert_ne!(m1,m2);
+    assert!(m1 < m2);
+    m2.remove(&9);
+    assert!(m1 > m2);
+
+    // maps as keys in other maps and sets
+    let mut by_map = BinTreeMap::new();
+    by_map.insert(m1.clone(), 1);
+    by_map.insert(m2.clone(), 2);
+    by_map.insert(m1.clone(), 3);
+    assert_eq!(by_map.len(),2);
+    assert_eq!(by_map.get(&m1),Some(&3));
+    let hashed : HashSet<_> = [m1.clone(), m2.clone(), m1.clone()].into_iter().collect();
+    assert_eq!(hashed.len(),2);
+    assert!(hashed.contains(&m1));
+}
+
+#[test]
+fn test_map_exact_size() {
+    let mut map = BinTreeMap::new();
+    (0..100).for_each(|i| { map.insert(i, i * 2); });
+    map.remove(&50);
+    let mut iter = map.iter();
+    assert_eq!(iter.len(),99);
+    iter.next();
+    assert_eq!(iter.len(),98);
+    assert_eq!(map.keys().len(),99);
+    assert_eq!(map.values().skip(9).len(),90);
+    assert_eq!(map.iter_mut().len(),99);
+    let mut into_iter = map.clone().into_iter();
+    assert_eq!(into_iter.by_ref().take(99).count(),99);
+    assert_eq!(into_iter.len(),0);
+    assert_eq!(into_iter.next(),None);
+    let pairs : Vec<_> = map.into_iter().collect();
+    assert_eq!(pairs.capacity(),99);
+}
+
+#[test]
+fn test_map_index_conversions() {
+    let mut map = BinTreeMap::from([("b", 2), ("a", 1), ("b", 3)]);
+    assert_eq!(map.len(),2);
+    assert_eq!(map["b"],3);
+    *map.get_or_insert_default("c") += 10;
+    *map.get_or_insert_default("a") += 10;
+    *map.get_or_insert_with("d", || 4) *= 2;
+    assert_eq!(map.to_string(),"[(\"a\", 11), (\"b\", 3), (\"c\", 10), (\"d\", 8)]");
+    assert_eq!(map.len(),4);
+    // NaN is an ordinary key with a total order (the largest one)
+    let mut floats = BinTreeMap::with_comparator(crate::FloatTotalOrder);
+    floats.extend([(1.0, 'a'), (2.0, 'b')]);
+    assert_eq!(*floats.get_or_insert_with(f64::NAN, || 'n'),'n');
+    assert_eq!(*floats.get_or_insert_with(f64::NAN, || unreachable!()),'n');
+    assert_eq!(floats.values().copied().collect::<String>(),"abn");
+    assert_eq!(floats.len(),3);
+
+    let mut words : BinTreeMap<String,usize> = vec![(String::from("x"), 1)].into();
+    assert_eq!(words["x"],1);
+    words.extend([(String::from("y"), 2)]);
+    let copied : BinTreeMap<i32,i32> = (0..3).map(|i| (i, -i)).collect();
+    let mut more = BinTreeMap::new();
+    more.extend(&copied);
+    more.extend(copied.iter());
+    assert_eq!(more,copied);
+    assert_eq!(words.len(),2);
+}
+
+#[test]
+#[should_panic(expected = "key not found")]
+fn test_map_index_missing() {
+    let map : BinTreeMap<i32,i32> = BinTreeMap::from([(1, 1)]);
+    let _ = map[&2];
+}
+
+#[test]
+fn test_map_unordered_keys() {
+    use crate::ByKey;
+
+    /// a key without PartialOrd, ordered by a projection
+    #[derive(Debug,Clone,PartialEq)]
+    struct Handle { fd : i32 }
+    fn by_fd(handle : &Handle) -> i32 { handle.fd }
+
+    let mut map = BinTreeMap::with_comparator(ByKey(by_fd as fn(&Handle) -> i32));
+    map.insert(Handle { fd : 4 }, "b");
+    map.insert(Handle { fd : 3 }, "a");
+    assert_eq!(map.get(&Handle { fd : 3 }),Some(&"a"));
+    assert_eq!(map.keys().map(|h| h.fd).collect::<Vec<_>>(),vec![3,4]);
+    assert_eq!(map.remove(&Handle { fd : 4 }),Some("b"));
+    assert_eq!(map.len(),1);
+}
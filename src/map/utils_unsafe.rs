@@ -1,8 +1,10 @@
-use crate::{BinTreeMap, Result, BinTreeError};
+use core::borrow::Borrow;
 
-impl<Key : PartialOrd, Value> BinTreeMap<Key,Value> {
+use crate::{BinTreeMap, Result, BinTreeError, Comparator};
+
+impl<Key, Value, C : Comparator<Key>> BinTreeMap<Key,Value,C> {
     /// swap 2 items in the map (lookup by key)
-    pub fn swap(&mut self, key1 : &Key, key2 : &Key) -> Result<()> {
+    pub fn swap<Q>(&mut self, key1 : &Q, key2 : &Q) -> Result<()> where Key : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
         let opt1 = self.get_mut(key1);
         if opt1.is_none() { return Err(BinTreeError::SwapNotFound1) }
         let ptr1 = opt1.unwrap() as * mut Value;
@@ -11,13 +13,18 @@ impl<Key : PartialOrd, Value> BinTreeMap<Key,Value> {
         let ptr2 = opt2.unwrap() as * mut Value;
         if ptr1 != ptr2 {
             unsafe {
-                std::ptr::swap(ptr1,ptr2);
+                core::ptr::swap(ptr1,ptr2);
             }
             Ok(())
         } else {
             Err(BinTreeError::SwapSame)
         }
     }
+    /// swap 2 items in the map (lookup by key)
+    /// same as swap but a missing key is reported as BinTreeError::KeyNotFound
+    pub fn try_swap<Q>(&mut self, key1 : &Q, key2 : &Q) -> Result<()> where Key : Borrow<Q>, Q : core::fmt::Debug + ?Sized, C : Comparator<Q> {
+        self.swap(key1, key2).map_err(|e| e.with_swap_keys(key1, key2))
+    }
 }
 
 /// some tests
@@ -55,11 +62,11 @@ mod test {
             (KeyType(3330), ValueType(-1782))\
         ]");
 
-        assert_eq!(t.swap(&KeyType(3330), &KeyType(130)).is_ok(),true);
-        assert_eq!(t.swap(&KeyType(12), &KeyType(-40)).is_ok(),true);
-        assert_eq!(t.swap(&KeyType(120), &KeyType(-40)).is_ok(),false);
-        assert_eq!(t.swap(&KeyType(12), &KeyType(-400)).is_ok(),false);
-        assert_eq!(t.swap(&KeyType(12), &KeyType(12)).is_ok(),false);
+        assert!(t.swap(&KeyType(3330), &KeyType(130)).is_ok());
+        assert!(t.swap(&KeyType(12), &KeyType(-40)).is_ok());
+        assert!(t.swap(&KeyType(120), &KeyType(-40)).is_err());
+        assert!(t.swap(&KeyType(12), &KeyType(-400)).is_err());
+        assert!(t.swap(&KeyType(12), &KeyType(12)).is_err());
 
         assert_eq!(t.to_string(),"[\
             (KeyType(-876), ValueType(-182)), \
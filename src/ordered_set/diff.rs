@@ -0,0 +1,41 @@
+use crate::{BinTreeOrderedSet, Comparator, MergeJoin, MergeJoinItem};
+
+/// item returned by BinTreeOrderedSet::diff (changes needed to go from self to other)
+#[derive(Debug,Clone,PartialEq)]
+pub enum SetDiff<T> {
+    Added(T),
+    Removed(T),
+}
+
+impl<T : Clone> SetDiff<&T> {
+    /// maps a borrowed diff item to an owned one (like Option::cloned)
+    pub fn cloned(self) -> SetDiff<T> {
+        match self {
+            SetDiff::Added(x) => SetDiff::Added(x.clone()),
+            SetDiff::Removed(x) => SetDiff::Removed(x.clone()),
+        }
+    }
+}
+
+impl<Item, C : Comparator<Item>> BinTreeOrderedSet<Item,C> {
+    /// lazy iterator over the differences between self and other in order
+    /// (Added: only in other, Removed: only in self)
+    pub fn diff<'a>(&'a self, other : &'a Self) -> impl Iterator<Item = SetDiff<&'a Item>> + 'a {
+        MergeJoin::new(self.iter().map(|x| (x,())), other.iter().map(|x| (x,())),
+                |x1 : &&Item, x2 : &&Item| self.cmp.compare(*x1, *x2))
+            .filter_map(|item| match item {
+                MergeJoinItem::Left(x,_) => Some(SetDiff::Removed(x)),
+                MergeJoinItem::Right(x,_) => Some(SetDiff::Added(x)),
+                MergeJoinItem::Both(..) => None,
+            })
+    }
+    /// applies a diff to the set
+    pub fn patch<I>(&mut self, diff : I) where I : IntoIterator<Item = SetDiff<Item>> {
+        for item in diff {
+            match item {
+                SetDiff::Added(x) => self.insert(x),
+                SetDiff::Removed(x) => { self.remove(&x); },
+            }
+        }
+    }
+}
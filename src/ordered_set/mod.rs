@@ -1,68 +1,213 @@
-use crate::{BinTree, BinTreeIntoIter, BinTreeIter};
+use alloc::{format, string::String, vec::Vec};
+use core::borrow::Borrow;
+
+use crate::{BinTree, BinTreeError, BinTreeExactSizeIter, BinTreeIntoIter, BinTreeIter, bin_tree::ExtractState, Comparator, NaturalOrder, Result, comparator::cmp_fn};
+
+/// differences between two sets (diff, patch)
+mod diff;
+pub use self::diff::*;
 
 /// a basic ordered set container shows how to encapsulate a type inside another
+/// items are ordered by the comparator (natural order by default)
 #[derive(Debug,Clone)]
-pub struct BinTreeOrderedSet<Item> where Item : PartialOrd {
+pub struct BinTreeOrderedSet<Item,C = NaturalOrder> {
     data: BinTree<Item>,
     len: usize,
+    cmp: C,
 }
 
 /// default set is an empty tree
-impl<Item : PartialOrd> Default for BinTreeOrderedSet<Item> {
+impl<Item, C : Default> Default for BinTreeOrderedSet<Item,C> {
     fn default() -> Self {
-        Self {
-            data: BinTree::default(),
-            len: 0
-        }
+        Self::with_comparator(C::default())
     }
 }
 
-impl<Item : PartialOrd> BinTreeOrderedSet<Item> {
+impl<Item> BinTreeOrderedSet<Item> {
     /// empty tree
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<Item, C> BinTreeOrderedSet<Item,C> {
+    /// empty tree ordered by a custom comparator
+    pub fn with_comparator(cmp : C) -> Self {
+        Self {
+            data: BinTree::default(),
+            len: 0,
+            cmp,
+        }
+    }
+    /// returns a ref to the comparator used to order the items
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
     /// number of elements in the set
     pub fn len(&self) -> usize {
         self.len
     }
-    /// set insertion (uses push_ordered_unique tree method)
+    /// is the set empty ?
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    /// set iterator (depth-first in-order tree iterator)
+    pub fn iter(&self) -> BinTreeExactSizeIter<BinTreeIter<'_, Item>> {
+        self.data.iter_dfs_in().with_len(self.len)
+    }
+    /// transforms the set into the wrapped binary tree
+    pub fn into_inner(self) -> BinTree<Item> {
+        self.data
+    }
+    /// returns a ref to the wrapped binary tree
+    pub fn inner(&self) -> &BinTree<Item> {
+        &self.data
+    }
+    pub fn to_tree_string(&self) -> String where Item : core::fmt::Debug {
+        format!("{}",self.inner())
+    }
+    /// keep only the values for which the predicate returns true
+    /// the tree is rebuilt balanced in linear time (uses extract_if)
+    pub fn retain<F>(&mut self, mut pred : F) where F : FnMut(&Item) -> bool {
+        self.extract_if(|x| !pred(x)).for_each(drop);
+    }
+    /// lazy iterator that removes and returns the values for which the predicate returns true
+    /// the remaining values are rebuilt into a balanced tree when the iterator is dropped (linear time)
+    pub fn extract_if<F>(&mut self, pred : F) -> BinTreeOrderedSetExtractIf<'_, Item, F> where F : FnMut(&Item) -> bool {
+        BinTreeOrderedSetExtractIf { state: ExtractState::new(&mut self.data, &mut self.len), pred }
+    }
+}
+
+/// extract_if iterator for BinTreeOrderedSet (uses BinTree extraction)
+pub struct BinTreeOrderedSetExtractIf<'a, Item, F> where F : FnMut(&Item) -> bool {
+    state: ExtractState<'a, Item>,
+    pred: F,
+}
+
+impl<Item, F> Iterator for BinTreeOrderedSetExtractIf<'_, Item, F> where F : FnMut(&Item) -> bool {
+    type Item = Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pred = &mut self.pred;
+        self.state.next_with(|x| pred(x))
+    }
+}
+
+impl<Item, C : Comparator<Item>> BinTreeOrderedSet<Item,C> {
+    /// set insertion (uses insert_to_key_cmp tree method)
+    /// a value that can't be compared (NaN for example) replaces an arbitrary value (see insert_checked)
     pub fn insert(&mut self, value : Item) {
-        if self.data.insert_unique(value) {
+        if self.data.insert_to_key_cmp(value, |x|x, cmp_fn(&self.cmp), true, true).is_none() {
             self.len += 1;
         }
     }
-    /// remove from set (uses remove_sorted tree method)
-    pub fn remove(&mut self, value : &Item) -> Option<Item> where Item : Default {
-        if let Some(removed) = self.data.remove_sorted(value) {
+    /// remove from set (uses remove_sorted_to_key_cmp tree method)
+    pub fn remove<Q>(&mut self, value : &Q) -> Option<Item> where Item : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        if let Some(removed) = self.data.remove_sorted_to_key_cmp(value, Item::borrow, cmp_fn(&self.cmp), true) {
             self.len -= 1;
             Some(removed)
         } else {
             None
         }
     }
-    /// find a value in the set (uses contains_sorted tree method)
-    pub fn contains(&self, value : &Item) -> bool {
-        self.data.contains_sorted(value)
+    /// remove the smallest value from the set (uses pop_min_sorted tree method)
+    pub fn pop_first(&mut self) -> Option<Item> {
+        let popped = self.data.pop_min_sorted(true);
+        if popped.is_some() {
+            self.len -= 1;
+        }
+        popped
     }
-    /// set iterator (depth-first in-order tree iterator)
-    pub fn iter(&self) -> BinTreeIter<Item> {
-        self.data.iter_dfs_in()
+    /// remove the largest value from the set (uses pop_max_sorted tree method)
+    pub fn pop_last(&mut self) -> Option<Item> {
+        let popped = self.data.pop_max_sorted(true);
+        if popped.is_some() {
+            self.len -= 1;
+        }
+        popped
     }
-    /// transforms the set into the wrapped binary tree
-    pub fn into_inner(self) -> BinTree<Item> {
-        self.data
+    /// find a value in the set (uses get_sorted_to_key_cmp tree method)
+    pub fn contains<Q>(&self, value : &Q) -> bool where Item : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        self.data.get_sorted_to_key_cmp(value, Item::borrow, cmp_fn(&self.cmp)).is_some()
     }
-    /// returns a ref to the wrapped binary tree
-    pub fn inner(&self) -> &BinTree<Item> {
-        &self.data
+    /// returns a ref to the stored value equal to a value (uses get_sorted_to_key_cmp tree method)
+    pub fn get<Q>(&self, value : &Q) -> Option<&Item> where Item : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        self.data.get_sorted_to_key_cmp(value, Item::borrow, cmp_fn(&self.cmp))
     }
-    pub fn to_tree_string(&self) -> String where Item : std::fmt::Debug {
-        format!("{}",self.inner())
+    /// removes and returns the stored value equal to a value (same as remove)
+    pub fn take<Q>(&mut self, value : &Q) -> Option<Item> where Item : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        self.remove(value)
+    }
+    /// inserts a value and returns the stored value it replaced (uses insert_to_key_cmp tree method)
+    pub fn replace(&mut self, value : Item) -> Option<Item> {
+        let replaced = self.data.insert_to_key_cmp(value, |x|x, cmp_fn(&self.cmp), true, true);
+        if replaced.is_none() {
+            self.len += 1;
+        }
+        replaced
+    }
+    /// set insertion (uses insert_to_key_cmp_checked tree method)
+    /// fails with BinTreeError::Incomparable if the value can't be compared (the set is left unchanged)
+    pub fn insert_checked(&mut self, value : Item) -> Result<()> {
+        if self.data.insert_to_key_cmp_checked(value, |x|x, cmp_fn(&self.cmp), true, true)?.is_none() {
+            self.len += 1;
+        }
+        Ok(())
+    }
+    /// remove from set (uses remove_sorted_to_key_cmp_checked tree method)
+    /// fails with BinTreeError::Incomparable if the value can't be compared (the set is left unchanged)
+    pub fn remove_checked<Q>(&mut self, value : &Q) -> Result<Option<Item>> where Item : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        let removed = self.data.remove_sorted_to_key_cmp_checked(value, Item::borrow, cmp_fn(&self.cmp), true)?;
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        Ok(removed)
+    }
+    /// find a value in the set (uses get_sorted_to_key_cmp_checked tree method)
+    /// fails with BinTreeError::Incomparable if the value can't be compared
+    pub fn contains_checked<Q>(&self, value : &Q) -> Result<bool> where Item : Borrow<Q>, Q : ?Sized, C : Comparator<Q> {
+        Ok(self.data.get_sorted_to_key_cmp_checked(value, Item::borrow, cmp_fn(&self.cmp))?.is_some())
+    }
+    /// insert a new value into the set
+    /// fails with BinTreeError::DuplicateKey if the value is already present (the set is left unchanged)
+    pub fn try_insert(&mut self, value : Item) -> Result<()> where Item : core::fmt::Debug {
+        if self.contains_checked(&value)? {
+            return Err(BinTreeError::duplicate_key(&value));
+        }
+        self.insert_checked(value)
+    }
+    /// remove from set and return the removed value
+    /// fails with BinTreeError::KeyNotFound if the value is not in the set
+    pub fn try_remove<Q>(&mut self, value : &Q) -> Result<Item> where Item : Borrow<Q>, Q : core::fmt::Debug + ?Sized, C : Comparator<Q> {
+        self.remove_checked(value)?.ok_or_else(|| BinTreeError::key_not_found(value))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<Item, C> BinTreeOrderedSet<Item,C> {
+    /// parallel set iterator (rayon), same order as iter when collected
+    pub fn par_iter(&self) -> impl rayon::iter::ParallelIterator<Item = &Item> where Item : Sync {
+        self.data.par_iter()
+    }
+    /// parallel consuming set iterator (rayon)
+    pub fn into_par_iter(self) -> impl rayon::iter::ParallelIterator<Item = Item> where Item : Send {
+        self.data.into_par_iter()
     }
 }
 
-impl<Item: PartialOrd> Extend<Item> for BinTreeOrderedSet<Item> {
+#[cfg(feature = "merkle")]
+impl<Item : core::hash::Hash, C> BinTreeOrderedSet<Item,C> {
+    /// merkle hash of the set (see BinTree::root_hash)
+    pub fn root_hash(&self) -> u64 {
+        self.data.root_hash()
+    }
+    /// compares the merkle hashes of two sets (O(1) when the hashes are cached)
+    pub fn merkle_eq(&self, other : &Self) -> bool {
+        self.data.merkle_eq(&other.data)
+    }
+}
+
+impl<Item, C : Comparator<Item>> Extend<Item> for BinTreeOrderedSet<Item,C> {
     /// extend a set from an iterator
     fn extend<T: IntoIterator<Item = Item>>(&mut self, iter: T) {
         for elem in iter {
@@ -71,14 +216,14 @@ impl<Item: PartialOrd> Extend<Item> for BinTreeOrderedSet<Item> {
     }
 }
 
-impl<Item : PartialOrd + std::fmt::Debug> std::fmt::Display for BinTreeOrderedSet<Item> {
+impl<Item : core::fmt::Debug, C> core::fmt::Display for BinTreeOrderedSet<Item,C> {
     /// display a set as a vector
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f,"{:?}",self.data.iter().collect::<Vec<_>>())
     }
 }
 
-impl<Item : PartialOrd> FromIterator<Item> for BinTreeOrderedSet<Item> {
+impl<Item, C : Comparator<Item> + Default> FromIterator<Item> for BinTreeOrderedSet<Item,C> {
     /// create a set from an iterator
     fn from_iter<T: IntoIterator<Item = Item>>(iter: T) -> Self {
         let mut s = Self::default();
@@ -87,42 +232,65 @@ impl<Item : PartialOrd> FromIterator<Item> for BinTreeOrderedSet<Item> {
     }
 }
 
-impl<T : PartialOrd> IntoIterator for BinTreeOrderedSet<T> {
-    type IntoIter = BinTreeIntoIter<T>;
+impl<T, C> IntoIterator for BinTreeOrderedSet<T,C> {
+    type IntoIter = BinTreeExactSizeIter<BinTreeIntoIter<T>>;
     type Item = T;
 
     /// set into_iter (depth-first in-order tree into_iter)
     fn into_iter(self) -> Self::IntoIter {
-        self.data.into_iter_dfs_in()
+        self.data.into_iter_dfs_in().with_len(self.len)
     }
 }
 
-impl<Item : PartialOrd> PartialEq for BinTreeOrderedSet<Item> {
-    /// set equality
+impl<Item : PartialEq, C> PartialEq for BinTreeOrderedSet<Item,C> {
+    /// set equality (same sorted items)
     fn eq(&self, other: &Self) -> bool {
-        self.iter().all(|e| other.contains(e)) &&
-        other.iter().all(|e| self.contains(e))
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<Item : Eq, C> Eq for BinTreeOrderedSet<Item,C> {}
+
+impl<Item : PartialOrd, C> PartialOrd for BinTreeOrderedSet<Item,C> {
+    /// lexicographic order of the sorted items
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<Item : Ord, C> Ord for BinTreeOrderedSet<Item,C> {
+    /// lexicographic order of the sorted items
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<Item : core::hash::Hash, C> core::hash::Hash for BinTreeOrderedSet<Item,C> {
+    /// hash of the length and the sorted items (consistent with eq)
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        self.iter().for_each(|item| item.hash(state));
     }
 }
 
-impl<Item : PartialOrd> From<BinTree<Item>> for BinTreeOrderedSet<Item> {
+impl<Item, C : Comparator<Item> + Default> From<BinTree<Item>> for BinTreeOrderedSet<Item,C> {
     /// create set from binary tree
     fn from(value: BinTree<Item>) -> Self {
-        Self::from_iter(value.into_iter())
+        Self::from_iter(value)
     }
 }
 
-impl<Item : PartialOrd> From<Vec<Item>> for BinTreeOrderedSet<Item> {
+impl<Item, C : Comparator<Item> + Default> From<Vec<Item>> for BinTreeOrderedSet<Item,C> {
     /// create set from vector
     fn from(value: Vec<Item>) -> Self {
-        Self::from_iter(value.into_iter())
+        Self::from_iter(value)
     }
 }
 
-impl<Item : PartialOrd> Into<BinTree<Item>> for BinTreeOrderedSet<Item> {
+impl<Item, C> From<BinTreeOrderedSet<Item,C>> for BinTree<Item> {
     /// transform set into wrapped binary tree
-    fn into(self) -> BinTree<Item> {
-        self.into_inner()
+    fn from(set: BinTreeOrderedSet<Item,C>) -> Self {
+        set.into_inner()
     }
 }
 
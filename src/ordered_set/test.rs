@@ -46,3 +46,156 @@ fn test_basic() {
     assert_eq!(str2," !,HJaeilmnosy");
     assert_eq!(s.len(),14);
 }
+
+#[test]
+fn test_comparator() {
+    use crate::{ByKey, ReverseOrder};
+
+    let s = TEST_STR.chars().collect::<BinTreeOrderedSet<_,ReverseOrder>>();
+    assert_eq!(s.iter().collect::<String>(),"ysonmlieaJH,! ");
+    assert!(s.contains(&'J'));
+    assert!(!s.contains(&'j'));
+
+    // one word per length: later words of the same length replace earlier ones
+    let mut by_len = BinTreeOrderedSet::with_comparator(ByKey(|s: &&str| s.len()));
+    by_len.extend(TEST_STR.split(' '));
+    assert_eq!(by_len.len(),3);
+    assert_eq!(by_len.to_string(),"[\"is\", \"Joe!\", \"Hello,\"]");
+    assert!(by_len.contains(&"abcd"));
+}
+
+#[test]
+fn test_incomparable() {
+    use crate::{BinTreeError, TotalOrder};
+
+    let mut s : BinTreeOrderedSet<f32> = vec![1.0,2.0].into();
+    assert_eq!(s.insert_checked(f32::NAN),Err(BinTreeError::Incomparable));
+    assert_eq!(s.contains_checked(&f32::NAN),Err(BinTreeError::Incomparable));
+    assert_eq!(s.remove_checked(&f32::NAN),Err(BinTreeError::Incomparable));
+    assert_eq!(s.insert_checked(3.0),Ok(()));
+    assert_eq!(s.remove_checked(&1.0),Ok(Some(1.0)));
+    assert_eq!(s.contains_checked(&1.0),Ok(false));
+    assert_eq!(s.to_string(),"[2.0, 3.0]");
+    assert_eq!(s.len(),2);
+
+    let s = TEST_STR.chars().collect::<BinTreeOrderedSet<_,TotalOrder>>();
+    assert_eq!(s.iter().collect::<String>()," !,HJaeilmnosy");
+}
+
+#[test]
+fn test_borrowed_lookup() {
+    let mut s : BinTreeOrderedSet<String> = TEST_STR.split(' ').map(String::from).collect();
+    assert!(s.contains("name"));
+    assert!(!s.contains("Joe"));
+    assert_eq!(s.contains_checked("Joe!"),Ok(true));
+    assert_eq!(s.remove("my"),Some(String::from("my")));
+    assert_eq!(s.remove_checked("is"),Ok(Some(String::from("is"))));
+    assert_eq!(s.to_string(),"[\"Hello,\", \"Joe!\", \"name\"]");
+}
+
+#[test]
+fn test_try_ops() {
+    use crate::BinTreeError;
+
+    let mut s = BinTreeOrderedSet::new();
+    assert_eq!(s.try_insert('a'),Ok(()));
+    assert_eq!(s.try_insert('b'),Ok(()));
+    assert_eq!(s.try_insert('a'),Err(BinTreeError::duplicate_key(&'a')));
+    assert_eq!(s.try_remove(&'a'),Ok('a'));
+    assert_eq!(s.try_remove(&'a'),Err(BinTreeError::KeyNotFound { key: String::from("'a'") }));
+    assert_eq!(s.len(),1);
+}
+
+#[test]
+fn test_retain_extract_if() {
+    let mut s : BinTreeOrderedSet<char> = TEST_STR.chars().collect();
+    s.retain(|c| c.is_alphabetic());
+    assert_eq!(s.iter().collect::<String>(),"HJaeilmnosy");
+    assert!(s.inner().is_balanced());
+    let upper : String = s.extract_if(|c| c.is_uppercase()).collect();
+    assert_eq!(upper,"HJ");
+    assert_eq!(s.len(),9);
+    assert!(!s.contains(&'H'));
+    assert!(s.inner().is_balanced());
+}
+
+#[test]
+fn test_diff_patch() {
+    use crate::SetDiff::*;
+
+    let mut s1 : BinTreeOrderedSet<char> = "abcde".chars().collect();
+    let s2 : BinTreeOrderedSet<char> = "bdefg".chars().collect();
+    assert_eq!(s1.diff(&s2).collect::<Vec<_>>(),vec![Removed(&'a'),Removed(&'c'),Added(&'f'),Added(&'g')]);
+    let delta : Vec<_> = s1.diff(&s2).map(|d| d.cloned()).collect();
+    s1.patch(delta);
+    assert!(s1 == s2);
+    assert_eq!(s1.len(),5);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_iter() {
+    use rayon::iter::ParallelIterator;
+
+    let s : BinTreeOrderedSet<char> = TEST_STR.chars().collect();
+    assert_eq!(s.par_iter().collect::<String>(),s.iter().collect::<String>());
+    assert_eq!(s.into_par_iter().filter(|c| c.is_uppercase()).collect::<String>(),"HJ");
+}
+
+#[test]
+#[allow(clippy::mutable_key_type)] // the merkle hash cache is not part of eq/hash
+fn test_eq_hash_ord() {
+    use std::collections::HashSet;
+
+    let s1 : BinTreeOrderedSet<i32> = (0..10).collect();
+    let s2 : BinTreeOrderedSet<i32> = (0..10).rev().collect();
+    assert_eq!(s1,s2);
+    assert_eq!(s1.cmp(&s2),core::cmp::Ordering::Equal);
+    let s3 : BinTreeOrderedSet<i32> = (0..9).collect();
+    assert!(s3 < s1);
+    let s4 : BinTreeOrderedSet<i32> = (1..2).collect();
+    assert!(s4 > s1);
+    let hashed : HashSet<_> = [s1.clone(), s2, s3].into_iter().collect();
+    assert_eq!(hashed.len(),2);
+    assert!(hashed.contains(&s1));
+}
+
+#[test]
+fn test_exact_size() {
+    let mut set : BinTreeOrderedSet<i32> = (0..50).collect();
+    set.remove(&10);
+    assert_eq!(set.iter().size_hint(),(49,Some(49)));
+    assert_eq!(set.iter().len(),49);
+    let mut into_iter = set.clone().into_iter();
+    into_iter.next();
+    assert_eq!(into_iter.len(),48);
+    assert_eq!(set.iter().skip(40).count(),9);
+}
+
+#[test]
+fn test_take_replace_get() {
+    use core::num::NonZeroU64;
+
+    // no Default for NonZeroU64
+    let mut ids : BinTreeOrderedSet<NonZeroU64> = (1..=5).filter_map(NonZeroU64::new).collect();
+    let three = NonZeroU64::new(3).unwrap();
+    assert_eq!(ids.remove(&three),Some(three));
+    assert_eq!(ids.take(&three),None);
+    assert_eq!(ids.len(),4);
+
+    /// equal by id only
+    #[derive(Debug,Clone)]
+    struct Tagged { id : u32, tag : &'static str }
+    impl PartialEq for Tagged { fn eq(&self, other : &Self) -> bool { self.id == other.id } }
+    impl PartialOrd for Tagged { fn partial_cmp(&self, other : &Self) -> Option<core::cmp::Ordering> { self.id.partial_cmp(&other.id) } }
+
+    let mut set = BinTreeOrderedSet::new();
+    assert!(set.replace(Tagged { id : 1, tag : "old" }).is_none());
+    let replaced = set.replace(Tagged { id : 1, tag : "new" }).unwrap();
+    assert_eq!(replaced.tag,"old");
+    assert_eq!(set.len(),1);
+    assert_eq!(set.get(&Tagged { id : 1, tag : "" }).unwrap().tag,"new");
+    assert!(set.get(&Tagged { id : 2, tag : "" }).is_none());
+    assert_eq!(set.take(&Tagged { id : 1, tag : "" }).unwrap().tag,"new");
+    assert!(set.is_empty());
+}
@@ -39,19 +39,19 @@ fn demo() {
 
     // false (because the tree is not sorted and this method depends on it for the search)
     // by wrapping in an ordered set or map, we avoid these issues (see below)
-    assert_eq!(t.contains_sorted(&2),false);
+    assert!(!t.contains_sorted(&2));
     
-    assert_eq!(t.contains(&2),true);
+    assert!(t.contains(&2));
     
     // true (happy accident: tree is partially sorted to the right)
     // by wrapping in an ordered set or map, we avoid these issues (see below)
-    assert_eq!(t.contains_sorted(&5),true);
+    assert!(t.contains_sorted(&5));
     
     // iterators with 4 traversal orders (breadth-first, depth-first: in, pre, post-order)
-    assert_eq!(t.iter_bfs().map(|x|x.clone()).collect::<Vec<_>>(),vec![1, 2, 4, 3, 5, 6]);
-    assert_eq!(t.iter_dfs_in().map(|x|x.clone()).collect::<Vec<_>>(),vec![3, 2, 1, 4, 6, 5]);
-    assert_eq!(t.iter_dfs_pre().map(|x|x.clone()).collect::<Vec<_>>(),vec![1, 2, 3, 4, 5, 6]);
-    assert_eq!(t.iter_dfs_post().map(|x|x.clone()).collect::<Vec<_>>(),vec![3, 2, 6, 5, 4, 1]);
+    assert_eq!(t.iter_bfs().copied().collect::<Vec<_>>(),vec![1, 2, 4, 3, 5, 6]);
+    assert_eq!(t.iter_dfs_in().copied().collect::<Vec<_>>(),vec![3, 2, 1, 4, 6, 5]);
+    assert_eq!(t.iter_dfs_pre().copied().collect::<Vec<_>>(),vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(t.iter_dfs_post().copied().collect::<Vec<_>>(),vec![3, 2, 6, 5, 4, 1]);
 
     // binary tree iter_mut
     for i in t.iter_mut() {
@@ -72,7 +72,7 @@ fn demo() {
     for i in t.to_vec() {
         assert_eq!(t.remove(&i),Some(i));    
     }
-    assert_eq!(t.is_empty(),true);
+    assert!(t.is_empty());
 
     // binary tree ordered set (always balanced as well as ordered)
     let v = vec![18,6,3,8,5,11,1,7,3,5,2,8,10,3,6,9,3,2];
@@ -83,9 +83,9 @@ fn demo() {
     assert_eq!(t.len(),11);
     assert_eq!(t.inner().height(),4);
         
-    assert_eq!(t.contains(&2),true);
-    assert_eq!(t.contains(&10),true);
-    assert_eq!(t.contains(&4),false);
+    assert!(t.contains(&2));
+    assert!(t.contains(&10));
+    assert!(!t.contains(&4));
     
     assert_eq!(t.remove(&7),Some(7));
     assert_eq!(format!("{}",t),"[1, 2, 3, 5, 6, 8, 9, 10, 11, 18]");
@@ -100,8 +100,8 @@ fn demo() {
     t.insert('c', 500);
     t.insert('b', 1782);
     assert_eq!(t.to_string(),"[('a', 782), ('b', 1782), ('c', 500)]");
-    assert_eq!(t.contains_key(&'b'),true);
-    assert_eq!(t.contains_key(&'z'),false);
+    assert!(t.contains_key(&'b'));
+    assert!(!t.contains_key(&'z'));
     assert_eq!(t.get(&'a'),Some(&782));
     assert_eq!(t.swap(&'a', &'c'),Ok(()));
     assert_eq!(t.to_string(),"[('a', 500), ('b', 1782), ('c', 782)]");
@@ -0,0 +1,128 @@
+// model-based tests: random operation sequences are applied both to our containers
+// and to the std collections, and the results are compared after every step
+// cargo test --test model_check (PROPTEST_CASES=n for longer runs), too slow for Miri
+#![cfg(not(miri))]
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bin_tree::{BinTree, BinTreeMap, BinTreeOrderedSet, BinTreeError};
+use proptest::prelude::*;
+
+/// a small key space makes collisions (overwrites, failed removes) frequent
+const KEY_SPACE : u8 = 64;
+
+#[derive(Debug, Clone)]
+enum MapOp {
+    Insert(u8, u32),
+    Remove(u8),
+    Get(u8),
+    GetMut(u8, u32),
+    Swap(u8, u8),
+    PopFirst,
+    PopLast,
+}
+
+fn map_op() -> impl Strategy<Value = MapOp> {
+    let key = || 0..KEY_SPACE;
+    prop_oneof![
+        4 => (key(), any::<u32>()).prop_map(|(k,v)| MapOp::Insert(k,v)),
+        2 => key().prop_map(MapOp::Remove),
+        1 => key().prop_map(MapOp::Get),
+        1 => (key(), any::<u32>()).prop_map(|(k,v)| MapOp::GetMut(k,v)),
+        1 => (key(), key()).prop_map(|(k1,k2)| MapOp::Swap(k1,k2)),
+        1 => Just(MapOp::PopFirst),
+        1 => Just(MapOp::PopLast),
+    ]
+}
+
+#[derive(Debug, Clone)]
+enum SetOp {
+    Insert(u8),
+    Remove(u8),
+    Contains(u8),
+    PopFirst,
+    PopLast,
+}
+
+fn set_op() -> impl Strategy<Value = SetOp> {
+    let key = || 0..KEY_SPACE;
+    prop_oneof![
+        4 => key().prop_map(SetOp::Insert),
+        2 => key().prop_map(SetOp::Remove),
+        1 => key().prop_map(SetOp::Contains),
+        1 => Just(SetOp::PopFirst),
+        1 => Just(SetOp::PopLast),
+    ]
+}
+
+/// heights must be exact and the tree must be balanced after every operation
+fn check_tree<T: Clone>(tree: &BinTree<T>) {
+    let mut copy = tree.clone();
+    assert!(!copy.recalculate_heights(), "stale heights");
+    assert!(tree.is_balanced(), "tree not balanced");
+}
+
+/// std swap semantics matching BinTreeMap::swap
+fn model_swap(model: &mut BTreeMap<u8,u32>, k1: u8, k2: u8) -> Result<(), BinTreeError> {
+    let v1 = *model.get(&k1).ok_or(BinTreeError::SwapNotFound1)?;
+    let v2 = *model.get(&k2).ok_or(BinTreeError::SwapNotFound2)?;
+    if k1 == k2 {
+        return Err(BinTreeError::SwapSame);
+    }
+    model.insert(k1, v2);
+    model.insert(k2, v1);
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn map_matches_btreemap(ops in prop::collection::vec(map_op(), 0..256)) {
+        let mut map = BinTreeMap::new();
+        let mut model = BTreeMap::new();
+        for op in ops {
+            match op {
+                MapOp::Insert(k,v) => {
+                    map.insert(k, v);
+                    model.insert(k, v);
+                },
+                MapOp::Remove(k) => prop_assert_eq!(map.remove(&k), model.remove(&k)),
+                MapOp::Get(k) => prop_assert_eq!(map.get(&k), model.get(&k)),
+                MapOp::GetMut(k,v) => {
+                    if let Some(value) = map.get_mut(&k) { *value = v; }
+                    if let Some(value) = model.get_mut(&k) { *value = v; }
+                },
+                MapOp::Swap(k1,k2) => prop_assert_eq!(map.swap(&k1, &k2), model_swap(&mut model, k1, k2)),
+                MapOp::PopFirst => prop_assert_eq!(map.pop_first(), model.pop_first()),
+                MapOp::PopLast => prop_assert_eq!(map.pop_last(), model.pop_last()),
+            }
+            prop_assert_eq!(map.len(), model.len());
+            prop_assert_eq!(map.is_empty(), model.is_empty());
+            prop_assert!(map.iter().eq(model.iter()));
+            check_tree(map.inner());
+        }
+    }
+
+    #[test]
+    fn set_matches_btreeset(ops in prop::collection::vec(set_op(), 0..256)) {
+        let mut set = BinTreeOrderedSet::new();
+        let mut model = BTreeSet::new();
+        for op in ops {
+            match op {
+                SetOp::Insert(k) => {
+                    set.insert(k);
+                    model.insert(k);
+                },
+                SetOp::Remove(k) => prop_assert_eq!(set.remove(&k), model.take(&k)),
+                SetOp::Contains(k) => prop_assert_eq!(set.contains(&k), model.contains(&k)),
+                SetOp::PopFirst => prop_assert_eq!(set.pop_first(), model.pop_first()),
+                SetOp::PopLast => prop_assert_eq!(set.pop_last(), model.pop_last()),
+            }
+            prop_assert_eq!(set.len(), model.len());
+            prop_assert_eq!(set.is_empty(), model.is_empty());
+            prop_assert!(set.iter().eq(model.iter()));
+            check_tree(set.inner());
+        }
+    }
+}